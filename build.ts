@@ -50,15 +50,7 @@ if (import.meta.main) {
         )
         .replace(
           "console.error(getStringFromWasm0(arg0, arg1));",
-          `
-            const msg = getStringFromWasm0(arg0, arg1);
-            if (msg.includes('DiagnosticBuffer(["')) {
-              const diagnostic = msg.split('DiagnosticBuffer(["')[1].split('"])')[0]
-              throw new Error(diagnostic);
-            } else {
-              throw new Error(msg);
-            }
-          `,
+          "throw new Error(getStringFromWasm0(arg0, arg1));",
         ),
     );
     await run(["deno", "fmt", "-q", "./dist/compiler.js"]);
@@ -1,13 +1,17 @@
 import { ensureDir } from "https://deno.land/std@0.180.0/fs/ensure_dir.ts";
 import { join } from "https://deno.land/std@0.180.0/path/mod.ts";
 import init, {
+  analyzeExports as analyzeExportsWasmFn,
   parcelCSS,
   parseDeps as parseDepsWasmFn,
   transform as transformWasmFn,
+  transformAsset as transformAssetWasmFn,
 } from "./dist/compiler.js";
 import wasm from "./dist/wasm.js";
 import type {
   DependencyDescriptor,
+  ExportDescriptor,
+  ParseDepsOptions,
   TransformCSSOptions,
   TransformCSSResult,
   TransformOptions,
@@ -54,19 +58,19 @@ export async function initWasm() {
     const cachePath = join(cacheDir, "compiler.wasm");
     if (await existsFile(cachePath)) {
       const file = await Deno.open(cachePath, { read: true });
-      await init(
-        new Response(file.readable, {
+      await init({
+        module_or_path: new Response(file.readable, {
           headers: [["Content-Type", "application/wasm"]],
         }),
-      );
+      });
     } else {
       const wasmData = await wasm();
-      await init(wasmData);
+      await init({ module_or_path: wasmData });
       await ensureDir(cacheDir);
       await Deno.writeFile(cachePath, new Uint8Array(wasmData));
     }
   } else {
-    await init(await wasm());
+    await init({ module_or_path: await wasm() });
   }
   wasmReady = true;
 }
@@ -85,12 +89,22 @@ async function getWasmReady() {
 export async function parseDeps(
   specifier: string,
   code: string,
-  options: Pick<TransformOptions, "importMap" | "lang"> = {},
+  options: ParseDepsOptions = {},
 ): Promise<DependencyDescriptor[]> {
   await getWasmReady();
   return parseDepsWasmFn(specifier, code, options);
 }
 
+/** Analyze the exports of the module without executing it. */
+export async function analyzeExports(
+  specifier: string,
+  code: string,
+  options: Pick<TransformOptions, "lang" | "errorRecovery"> = {},
+): Promise<ExportDescriptor[]> {
+  await getWasmReady();
+  return analyzeExportsWasmFn(specifier, code, options);
+}
+
 /**
  * Transforms the JSX/TS module into a JS module.
  *
@@ -138,6 +152,20 @@ export async function transform(
   }
 }
 
+/**
+ * Transforms the JSON, text and binary asset into an ES module, e.g.
+ * `export default "data:image/png;base64,..."`, the other modules are transformed
+ * as `transform` does.
+ */
+export async function transformAsset(
+  specifier: string,
+  data: Uint8Array,
+  options: TransformOptions = {},
+): Promise<TransformResult> {
+  await getWasmReady();
+  return transformAssetWasmFn(specifier, data, options);
+}
+
 /**
  * Compiles a CSS file, including optionally minifying and lowering syntax to the given
  * targets. A source map may also be generated, but this is not enabled by default.
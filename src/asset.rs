@@ -0,0 +1,200 @@
+use crate::error::{Diagnostic, Error, Severity};
+use crate::resolver::is_http_url;
+use crate::TransformOutput;
+use base64::Engine;
+use serde_json::Value;
+use swc_ecmascript::ast::Ident;
+
+/// The assets smaller than the limit are inlined as data URLs by default.
+pub const DEFAULT_INLINE_LIMIT: usize = 4096;
+
+/// The kind of a non-JS module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AssetKind {
+  /// `*.json`, the top-level keys are exported as well as the default export.
+  Json,
+  /// `*.txt`, `*.md` or any file imported with `?raw`, exported as a string.
+  Text,
+  /// images, fonts, media and wasm files with the MIME type, exported as a data URL or the file URL.
+  Binary(&'static str),
+}
+
+/// Get the asset kind by the `lang` option or the extension of the specifier, returns `None`
+/// for JS/TS modules.
+pub fn get_asset_kind(specifier: &str, lang: Option<&str>) -> Option<AssetKind> {
+  let specifier = specifier.split('#').next().unwrap();
+  let (path, query) = match specifier.split_once('?') {
+    Some((path, query)) => (path, Some(query)),
+    None => (specifier, None),
+  };
+  if query
+    .map(|query| query.split('&').any(|param| param == "raw"))
+    .unwrap_or_default()
+  {
+    return Some(AssetKind::Text);
+  }
+  let ext = match lang {
+    Some(lang) => lang.to_lowercase(),
+    None => match path.rsplit_once('.') {
+      Some((_, ext)) if !ext.contains('/') => ext.to_lowercase(),
+      _ => return None,
+    },
+  };
+  let kind = match ext.as_str() {
+    "json" => AssetKind::Json,
+    "txt" | "md" | "html" | "htm" | "csv" | "xml" | "glsl" | "wgsl" => AssetKind::Text,
+    "svg" => AssetKind::Binary("image/svg+xml"),
+    "png" => AssetKind::Binary("image/png"),
+    "jpg" | "jpeg" => AssetKind::Binary("image/jpeg"),
+    "gif" => AssetKind::Binary("image/gif"),
+    "webp" => AssetKind::Binary("image/webp"),
+    "avif" => AssetKind::Binary("image/avif"),
+    "ico" => AssetKind::Binary("image/x-icon"),
+    "woff" => AssetKind::Binary("font/woff"),
+    "woff2" => AssetKind::Binary("font/woff2"),
+    "ttf" => AssetKind::Binary("font/ttf"),
+    "otf" => AssetKind::Binary("font/otf"),
+    "mp3" => AssetKind::Binary("audio/mpeg"),
+    "wav" => AssetKind::Binary("audio/wav"),
+    "ogg" => AssetKind::Binary("audio/ogg"),
+    "mp4" => AssetKind::Binary("video/mp4"),
+    "webm" => AssetKind::Binary("video/webm"),
+    "wasm" => AssetKind::Binary("application/wasm"),
+    _ => return None,
+  };
+  Some(kind)
+}
+
+/// Transform the asset into an ES module.
+pub fn transform(
+  specifier: &str,
+  data: &[u8],
+  kind: AssetKind,
+  inline_limit: usize,
+  minify: bool,
+) -> Result<TransformOutput, Error> {
+  let code = match kind {
+    AssetKind::Json => {
+      let value: Value = serde_json::from_slice(data).map_err(|err| {
+        Error::Diagnostics(vec![Diagnostic {
+          message: format!("Invalid JSON: {}", err),
+          specifier: specifier.into(),
+          severity: Severity::Error,
+          code: Some("InvalidJson".into()),
+          line: Some(err.line()),
+          column: Some(err.column().saturating_sub(1)),
+          span: None,
+          code_frame: None,
+        }])
+      })?;
+      json_module(&value, minify)
+    }
+    AssetKind::Text => default_export(&to_js_string(&String::from_utf8_lossy(data)), minify),
+    AssetKind::Binary(mime) => {
+      let url = if data.len() <= inline_limit {
+        format!(
+          "data:{};base64,{}",
+          mime,
+          base64::engine::general_purpose::STANDARD.encode(data)
+        )
+      } else {
+        to_file_url(specifier)
+      };
+      default_export(&to_js_string(&url), minify)
+    }
+  };
+  Ok(TransformOutput {
+    code,
+    deps: vec![],
+    map: None,
+    directives: vec![],
+    warnings: vec![],
+    errors: vec![],
+  })
+}
+
+// export const name = "aleph";
+// export default { name, "dev-deps": {} };
+fn json_module(value: &Value, minify: bool) -> String {
+  let map = match value {
+    Value::Object(map) => map,
+    value => return default_export(&to_js_value(value, minify), minify),
+  };
+  let (eq, nl) = if minify { ("=", "") } else { (" = ", "\n") };
+  let mut code = String::new();
+  let mut props = vec![];
+  for (key, value) in map {
+    if is_export_name(key) {
+      code.push_str(&format!(
+        "export const {}{}{};{}",
+        key,
+        eq,
+        to_js_value(value, minify),
+        nl
+      ));
+      props.push(key.to_owned());
+    } else {
+      props.push(format!(
+        "{}:{}{}",
+        to_js_string(key),
+        if minify { "" } else { " " },
+        to_js_value(value, minify)
+      ));
+    }
+  }
+  if props.is_empty() {
+    code.push_str(&default_export("{}", minify));
+  } else if minify {
+    code.push_str(&format!("export default{{{}}};", props.join(",")));
+  } else {
+    let props: Vec<String> = props
+      .iter()
+      .map(|prop| format!("  {},\n", prop.replace('\n', "\n  ")))
+      .collect();
+    code.push_str(&format!("export default {{\n{}}};\n", props.concat()));
+  }
+  code
+}
+
+fn default_export(expr: &str, minify: bool) -> String {
+  if minify {
+    format!("export default {};", expr)
+  } else {
+    format!("export default {};\n", expr)
+  }
+}
+
+// JSON is valid JS except the `__proto__` keys that would set the prototype of the object literal,
+// use `JSON.parse` for them.
+fn to_js_value(value: &Value, minify: bool) -> String {
+  let json = if minify {
+    serde_json::to_string(value)
+  } else {
+    serde_json::to_string_pretty(value)
+  }
+  .unwrap();
+  if json.contains("\"__proto__\"") {
+    format!("JSON.parse({})", to_js_string(&serde_json::to_string(value).unwrap()))
+  } else {
+    json
+  }
+}
+
+fn to_js_string(s: &str) -> String {
+  serde_json::to_string(s).unwrap()
+}
+
+// the keys that can be exported as `export const {key}`
+fn is_export_name(key: &str) -> bool {
+  Ident::verify_symbol(key).is_ok()
+}
+
+// the URL of the asset file, e.g. `./assets/logo.png?v=1` -> `/assets/logo.png`
+fn to_file_url(specifier: &str) -> String {
+  let path = specifier.split(['?', '#']).next().unwrap();
+  if is_http_url(path) {
+    path.to_owned()
+  } else {
+    "/".to_owned() + path.trim_start_matches('.').trim_start_matches('/')
+  }
+}
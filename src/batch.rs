@@ -0,0 +1,18 @@
+use crate::{transform_prepared, Error, Options, PreparedOptions, TransformOutput};
+use rayon::prelude::*;
+
+/// Transform the modules concurrently with the same options, the import map and the graph versions are
+/// prepared once and shared by all the modules. Each module is a `(specifier, code)` pair, the results
+/// are in the same order as the modules. Returns an error if the options are invalid.
+pub fn transform_batch(
+  modules: &[(String, String)],
+  options: &Options,
+) -> Result<Vec<Result<TransformOutput, Error>>, Error> {
+  let prepared = PreparedOptions::new(options)?;
+  Ok(
+    modules
+      .par_iter()
+      .map(|(specifier, code)| transform_prepared(specifier, code, options, &prepared))
+      .collect(),
+  )
+}
@@ -0,0 +1,245 @@
+use crate::{transform, Error, Options, TransformOutput};
+use serde_json::{Map, Value};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// A content-addressed cache of the transform outputs, keyed by the hash of the specifier, the source code,
+/// the options and the compiler version.
+///
+/// The outputs are kept in memory up to `max_size` bytes, the least recently used ones are evicted first.
+/// With `with_dir` the outputs are also stored on disk and survive restarts, failing to read or write the
+/// cache directory is not an error, the module is just transformed again. Only the latest output of a
+/// specifier is kept on disk, so the directory doesn't grow with every edit of the source code.
+///
+/// The `resolve_hook` of the options is not a part of the key, it must return the same result for the same
+/// specifier as long as the cache is used, or the cache should be cleared.
+pub struct TransformCache {
+  dir: Option<PathBuf>,
+  max_size: usize,
+  memory: Mutex<MemoryCache>,
+}
+
+#[derive(Default)]
+struct MemoryCache {
+  entries: HashMap<String, Entry>,
+  // the keys of the entries ordered by the last use, the least recently used first
+  recency: BTreeMap<u64, String>,
+  size: usize,
+  tick: u64,
+}
+
+struct Entry {
+  specifier: String,
+  output: TransformOutput,
+  size: usize,
+  last_used: u64,
+}
+
+impl TransformCache {
+  /// Create an in-memory cache that holds up to `max_size` bytes of code and source maps.
+  pub fn new(max_size: usize) -> Self {
+    TransformCache {
+      dir: None,
+      max_size,
+      memory: Mutex::new(MemoryCache::default()),
+    }
+  }
+
+  /// Store the outputs in the `aleph-compiler` subdirectory of the directory as well, it's created
+  /// if it doesn't exist.
+  pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.dir = Some(dir.into().join("aleph-compiler"));
+    self
+  }
+
+  /// Transform the module, or return the stored output if the module was transformed with the same
+  /// source code and options before. Errors are not cached.
+  pub fn transform(&self, specifier: &str, code: &str, options: &Options) -> Result<TransformOutput, Error> {
+    let key = cache_key(specifier, code, options);
+    if let Some(output) = self.get_memory(&key) {
+      return Ok(output);
+    }
+    if let Some(output) = self.read_disk(specifier, &key) {
+      self.put_memory(key, specifier, output.clone());
+      return Ok(output);
+    }
+    let output = transform(specifier, code, options)?;
+    self.write_disk(specifier, &key, &output);
+    self.put_memory(key, specifier, output.clone());
+    Ok(output)
+  }
+
+  /// Remove all the stored outputs of the specifier.
+  pub fn invalidate(&self, specifier: &str) {
+    let mut memory = self.memory.lock().unwrap();
+    let mut size = memory.size;
+    let mut removed = vec![];
+    memory.entries.retain(|_, entry| {
+      if entry.specifier == specifier {
+        size -= entry.size;
+        removed.push(entry.last_used);
+        return false;
+      }
+      true
+    });
+    for last_used in removed {
+      memory.recency.remove(&last_used);
+    }
+    memory.size = size;
+    if let Some(dir) = &self.dir {
+      fs::remove_dir_all(dir.join(hash(&[specifier]))).ok();
+    }
+  }
+
+  /// Remove all the stored outputs, including the ones on disk. Other files in the directory given to
+  /// `with_dir` are kept.
+  pub fn clear(&self) {
+    let mut memory = self.memory.lock().unwrap();
+    memory.entries.clear();
+    memory.recency.clear();
+    memory.size = 0;
+    if let Some(dir) = &self.dir {
+      fs::remove_dir_all(dir).ok();
+    }
+  }
+
+  /// The size in bytes of the outputs held in memory.
+  pub fn size(&self) -> usize {
+    self.memory.lock().unwrap().size
+  }
+
+  fn get_memory(&self, key: &str) -> Option<TransformOutput> {
+    let mut memory = self.memory.lock().unwrap();
+    memory.tick += 1;
+    let tick = memory.tick;
+    let entry = memory.entries.get_mut(key)?;
+    let last_used = std::mem::replace(&mut entry.last_used, tick);
+    let output = entry.output.clone();
+    memory.recency.remove(&last_used);
+    memory.recency.insert(tick, key.into());
+    Some(output)
+  }
+
+  fn put_memory(&self, key: String, specifier: &str, output: TransformOutput) {
+    let size = specifier.len() + output.code.len() + output.map.as_ref().map(|map| map.len()).unwrap_or_default();
+    if size > self.max_size {
+      return;
+    }
+    let mut memory = self.memory.lock().unwrap();
+    memory.tick += 1;
+    let tick = memory.tick;
+    if let Some(entry) = memory.entries.remove(&key) {
+      memory.size -= entry.size;
+      memory.recency.remove(&entry.last_used);
+    }
+    // evict the least recently used entries
+    while memory.size + size > self.max_size {
+      let lru = match memory.recency.pop_first() {
+        Some((_, key)) => key,
+        None => break,
+      };
+      if let Some(entry) = memory.entries.remove(&lru) {
+        memory.size -= entry.size;
+      }
+    }
+    memory.size += size;
+    memory.recency.insert(tick, key.clone());
+    memory.entries.insert(
+      key,
+      Entry {
+        specifier: specifier.into(),
+        output,
+        size,
+        last_used: tick,
+      },
+    );
+  }
+
+  fn disk_path(&self, specifier: &str, key: &str) -> Option<PathBuf> {
+    self
+      .dir
+      .as_ref()
+      .map(|dir| dir.join(hash(&[specifier])).join(key.to_owned() + ".json"))
+  }
+
+  fn read_disk(&self, specifier: &str, key: &str) -> Option<TransformOutput> {
+    let path = self.disk_path(specifier, key)?;
+    let data = fs::read(path).ok()?;
+    serde_json::from_slice(&data).ok()
+  }
+
+  fn write_disk(&self, specifier: &str, key: &str, output: &TransformOutput) {
+    if let Some(path) = self.disk_path(specifier, key) {
+      if write_file(&path, output).is_ok() {
+        remove_stale_files(&path);
+      }
+    }
+  }
+}
+
+// remove the older outputs of the specifier, the temporary files of concurrent writers are kept.
+fn remove_stale_files(path: &Path) {
+  let entries = match path.parent().map(fs::read_dir) {
+    Some(Ok(entries)) => entries,
+    _ => return,
+  };
+  for entry in entries.filter_map(|entry| entry.ok()) {
+    let stale = entry.path();
+    if stale != path && stale.extension().is_some_and(|ext| ext == "json") {
+      fs::remove_file(stale).ok();
+    }
+  }
+}
+
+// the unique suffix of the temporary files, the threads of the process may write the same output at once.
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// write to a temporary file first then rename it, so a concurrent reader never sees a partial file.
+fn write_file(path: &Path, output: &TransformOutput) -> std::io::Result<()> {
+  let dir = path.parent().unwrap();
+  fs::create_dir_all(dir)?;
+  let tmp = path.with_extension(format!(
+    "{}.{}.tmp",
+    std::process::id(),
+    TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+  ));
+  fs::write(&tmp, serde_json::to_vec(output)?)?;
+  fs::rename(&tmp, path)
+}
+
+fn cache_key(specifier: &str, code: &str, options: &Options) -> String {
+  let options = canonicalize(serde_json::to_value(options).unwrap_or_default()).to_string();
+  hash(&[env!("CARGO_PKG_VERSION"), specifier, &options, code])
+}
+
+fn hash(parts: &[&str]) -> String {
+  let mut hasher = Sha1::new();
+  for part in parts {
+    // the length prefix keeps `("ab", "c")` and `("a", "bc")` apart
+    hasher.update((part.len() as u64).to_le_bytes());
+    hasher.update(part.as_bytes());
+  }
+  format!("{:x}", hasher.finalize())
+}
+
+// sort the object keys, the `define` and `graphVersions` maps are serialized in random order.
+fn canonicalize(value: Value) -> Value {
+  match value {
+    Value::Object(map) => {
+      let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+      entries.sort_by(|a, b| a.0.cmp(&b.0));
+      Value::Object(
+        entries
+          .into_iter()
+          .map(|(key, value)| (key, canonicalize(value)))
+          .collect::<Map<String, Value>>(),
+      )
+    }
+    Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+    value => value,
+  }
+}
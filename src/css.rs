@@ -5,6 +5,7 @@
   ! below code was copied from https://github.com/parcel-bundler/parcel-css/blob/510df4e2d825927115427b690d6706da395d2170/node/src/lib.rs, and removed node napi code
 */
 
+use crate::error::{Diagnostic, Severity};
 use lightningcss::css_modules::CssModuleExports;
 use lightningcss::dependencies::Dependency;
 use lightningcss::error::{Error, MinifyErrorKind, ParserError, PrinterErrorKind};
@@ -32,16 +33,18 @@ pub struct TransformResult {
   pub map: Option<String>,
   pub exports: Option<CssModuleExports>,
   pub dependencies: Option<Vec<Dependency>>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<Diagnostic>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyOptions {
   /// Whether to remove `@import` rules.
   pub remove_imports: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
   pub targets: Option<Browsers>,
@@ -52,6 +55,7 @@ pub struct Config {
   pub analyze_dependencies: Option<DependencyOptions>,
   pub pseudo_classes: Option<OwnedPseudoClasses>,
   pub unused_symbols: Option<HashSet<String>>,
+  pub error_recovery: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -61,15 +65,15 @@ pub enum CssModulesOption {
   Config(CssModulesConfig),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CssModulesConfig {
-  pattern: Option<String>,
+  pub pattern: Option<String>,
   #[serde(default)]
-  dashed_idents: bool,
+  pub dashed_idents: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OwnedPseudoClasses {
   pub hover: Option<String>,
@@ -79,14 +83,14 @@ pub struct OwnedPseudoClasses {
   pub focus_within: Option<String>,
 }
 
-impl<'a> Into<PseudoClasses<'a>> for &'a OwnedPseudoClasses {
-  fn into(self) -> PseudoClasses<'a> {
+impl<'a> From<&'a OwnedPseudoClasses> for PseudoClasses<'a> {
+  fn from(pseudo_classes: &'a OwnedPseudoClasses) -> Self {
     PseudoClasses {
-      hover: self.hover.as_deref(),
-      active: self.active.as_deref(),
-      focus: self.focus.as_deref(),
-      focus_visible: self.focus_visible.as_deref(),
-      focus_within: self.focus_within.as_deref(),
+      hover: pseudo_classes.hover.as_deref(),
+      active: pseudo_classes.active.as_deref(),
+      focus: pseudo_classes.focus.as_deref(),
+      focus_visible: pseudo_classes.focus_visible.as_deref(),
+      focus_within: pseudo_classes.focus_within.as_deref(),
     }
   }
 }
@@ -102,9 +106,9 @@ pub struct Drafts {
 
 pub fn compile<'i>(filename: String, code: &'i str, config: &Config) -> Result<TransformResult, CompileError<'i>> {
   let drafts = config.drafts.as_ref();
-  let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+  let warnings = Arc::new(RwLock::new(Vec::new()));
   let mut stylesheet = StyleSheet::parse(
-    &code,
+    code,
     ParserOptions {
       filename: filename.clone(),
       nesting: matches!(drafts, Some(d) if d.nesting),
@@ -124,8 +128,8 @@ pub fn compile<'i>(filename: String, code: &'i str, config: &Config) -> Result<T
         None
       },
       source_index: 0,
-      error_recovery: false,
-      warnings: warnings.clone(),
+      error_recovery: config.error_recovery.unwrap_or_default(),
+      warnings: Some(warnings.clone()),
     },
   )?;
   stylesheet.minify(MinifyOptions {
@@ -147,13 +151,11 @@ pub fn compile<'i>(filename: String, code: &'i str, config: &Config) -> Result<T
     source_map: source_map.as_mut(),
     targets: config.targets,
     project_root: None,
-    analyze_dependencies: if let Some(analyze_dependencies) = &config.analyze_dependencies {
-      Some(lightningcss::dependencies::DependencyOptions {
+    analyze_dependencies: config.analyze_dependencies.as_ref().map(|analyze_dependencies| {
+      lightningcss::dependencies::DependencyOptions {
         remove_imports: analyze_dependencies.remove_imports,
-      })
-    } else {
-      None
-    },
+      }
+    }),
     pseudo_classes: config.pseudo_classes.as_ref().map(|p| p.into()),
   })?;
 
@@ -163,14 +165,36 @@ pub fn compile<'i>(filename: String, code: &'i str, config: &Config) -> Result<T
     None
   };
 
+  let warnings = warnings
+    .read()
+    .unwrap()
+    .iter()
+    .map(|warning| to_diagnostic(&filename, warning))
+    .collect();
+
   Ok(TransformResult {
     code: res.code,
     map,
     exports: res.exports,
     dependencies: res.dependencies,
+    warnings,
   })
 }
 
+fn to_diagnostic(filename: &str, warning: &Error<ParserError>) -> Diagnostic {
+  Diagnostic {
+    message: warning.kind.to_string(),
+    specifier: filename.into(),
+    severity: Severity::Warning,
+    code: None,
+    // lightningcss uses 0-based lines and 1-based columns
+    line: warning.loc.as_ref().map(|loc| loc.line as usize + 1),
+    column: warning.loc.as_ref().map(|loc| (loc.column as usize).saturating_sub(1)),
+    span: None,
+    code_frame: None,
+  }
+}
+
 #[inline]
 fn source_map_to_json<'i>(source_map: &mut SourceMap) -> Result<String, CompileError<'i>> {
   let mut vlq_output: Vec<u8> = Vec::new();
@@ -187,6 +211,7 @@ fn source_map_to_json<'i>(source_map: &mut SourceMap) -> Result<String, CompileE
   Ok(serde_json::to_string(&sm).unwrap())
 }
 
+#[allow(dead_code)]
 #[derive(Serialize, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AttrConfig {
@@ -196,6 +221,7 @@ struct AttrConfig {
   pub analyze_dependencies: Option<bool>,
 }
 
+#[allow(dead_code)]
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct AttrResult {
@@ -203,6 +229,7 @@ struct AttrResult {
   dependencies: Option<Vec<Dependency>>,
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum CompileError<'i> {
   ParseError(Error<ParserError<'i>>),
@@ -246,11 +273,11 @@ impl<'i> From<parcel_sourcemap::SourceMapError> for CompileError<'i> {
   }
 }
 
-impl<'i> From<CompileError<'i>> for wasm_bindgen::JsValue {
-  fn from(e: CompileError) -> wasm_bindgen::JsValue {
+impl<'i> From<CompileError<'i>> for crate::error::Error {
+  fn from(e: CompileError) -> crate::error::Error {
     match e {
-      CompileError::SourceMapError(e) => js_sys::Error::new(&e.to_string()).into(),
-      _ => js_sys::Error::new(&e.reason()).into(),
+      CompileError::SourceMapError(e) => crate::error::Error::Css(e.to_string()),
+      _ => crate::error::Error::Css(e.reason()),
     }
   }
 }
@@ -0,0 +1,133 @@
+use crate::swc_helpers::new_str;
+use serde_json::Value;
+use std::collections::HashMap;
+use swc_common::{Mark, DUMMY_SP};
+use swc_ecmascript::ast::*;
+use swc_ecmascript::utils::quote_ident;
+use swc_ecmascript::visit::{noop_fold_type, Fold, FoldWith};
+
+/// Replaces the global identifiers and member chains with the defined values, e.g.
+/// `process.env.NODE_ENV`, `import.meta.env.MODE` and `__DEV__`.
+pub fn define(defines: &HashMap<String, Value>, unresolved_mark: Mark) -> impl Fold {
+  DefineFold {
+    defines: defines
+      .iter()
+      .map(|(name, value)| (name.to_owned(), json_to_expr(value)))
+      .collect(),
+    unresolved_mark,
+  }
+}
+
+pub struct DefineFold {
+  defines: HashMap<String, Expr>,
+  unresolved_mark: Mark,
+}
+
+impl DefineFold {
+  // get the dotted name of the expression, `None` if it's not a global identifier or member chain.
+  fn get_name(&self, expr: &Expr) -> Option<String> {
+    match expr {
+      Expr::Ident(id) if id.span.ctxt.outer() == self.unresolved_mark => Some(id.sym.to_string()),
+      Expr::MetaProp(MetaPropExpr {
+        kind: MetaPropKind::ImportMeta,
+        ..
+      }) => Some("import.meta".into()),
+      Expr::Member(MemberExpr { obj, prop, .. }) => {
+        let prop = match prop {
+          MemberProp::Ident(id) => id.sym.to_string(),
+          MemberProp::Computed(ComputedPropName { expr, .. }) => match expr.as_ref() {
+            Expr::Lit(Lit::Str(s)) => s.value.to_string(),
+            _ => return None,
+          },
+          MemberProp::PrivateName(_) => return None,
+        };
+        self.get_name(obj).map(|obj| obj + "." + &prop)
+      }
+      _ => None,
+    }
+  }
+}
+
+impl Fold for DefineFold {
+  noop_fold_type!();
+
+  fn fold_expr(&mut self, expr: Expr) -> Expr {
+    if let Some(value) = self.get_name(&expr).and_then(|name| self.defines.get(&name)) {
+      return value.clone();
+    }
+    expr.fold_children_with(self)
+  }
+
+  // match: { __DEV__ }
+  fn fold_prop(&mut self, prop: Prop) -> Prop {
+    match prop {
+      Prop::Shorthand(id) => match self
+        .get_name(&Expr::Ident(id.clone()))
+        .and_then(|name| self.defines.get(&name))
+      {
+        Some(value) => Prop::KeyValue(KeyValueProp {
+          key: PropName::Ident(id),
+          value: Box::new(value.clone()),
+        }),
+        None => Prop::Shorthand(id),
+      },
+      prop => prop.fold_children_with(self),
+    }
+  }
+
+  // don't replace the assignment targets, e.g. `process.env.NODE_ENV = "test"`
+  fn fold_assign_expr(&mut self, expr: AssignExpr) -> AssignExpr {
+    AssignExpr {
+      right: expr.right.fold_with(self),
+      ..expr
+    }
+  }
+
+  fn fold_update_expr(&mut self, expr: UpdateExpr) -> UpdateExpr {
+    expr
+  }
+}
+
+fn json_to_expr(value: &Value) -> Expr {
+  match value {
+    Value::Null => Expr::Lit(Lit::Null(Null { span: DUMMY_SP })),
+    Value::Bool(value) => Expr::Lit(Lit::Bool(Bool {
+      span: DUMMY_SP,
+      value: *value,
+    })),
+    Value::Number(n) => Expr::Lit(Lit::Num(Number {
+      span: DUMMY_SP,
+      value: n.as_f64().unwrap_or_default(),
+      raw: None,
+    })),
+    Value::String(s) => Expr::Lit(Lit::Str(new_str(s))),
+    Value::Array(values) => Expr::Array(ArrayLit {
+      span: DUMMY_SP,
+      elems: values
+        .iter()
+        .map(|value| {
+          Some(ExprOrSpread {
+            spread: None,
+            expr: Box::new(json_to_expr(value)),
+          })
+        })
+        .collect(),
+    }),
+    Value::Object(map) => Expr::Object(ObjectLit {
+      span: DUMMY_SP,
+      props: map
+        .iter()
+        .map(|(key, value)| {
+          PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: if Ident::verify_symbol(key).is_ok() {
+              PropName::Ident(quote_ident!(key.as_str()))
+            } else {
+              PropName::Str(new_str(key))
+            },
+            value: Box::new(json_to_expr(value)),
+          })))
+        })
+        .collect(),
+    }),
+  }
+}
@@ -1,12 +1,18 @@
+use serde::{Deserialize, Serialize};
 use std::{fmt, sync::Arc, sync::RwLock};
-use swc_common::errors::{Diagnostic, DiagnosticBuilder, Emitter};
-use swc_common::{Loc, Span};
+use swc_common::errors::{DiagnosticBuilder, DiagnosticId, Emitter, Handler, HandlerFlags, Level, HANDLER};
+use swc_common::{BytePos, SourceFile, SourceMap, Span};
+use swc_ecmascript::parser::error::SyntaxError;
+use unicode_width::UnicodeWidthChar;
+
+/// The count of lines to show before and after the offending line in a code frame.
+const CODE_FRAME_CONTEXT_LINES: usize = 2;
 
 /// A buffer for collecting errors from the AST parser.
 #[derive(Debug, Clone)]
 pub struct ErrorBuffer {
   specifier: String,
-  diagnostics: Arc<RwLock<Vec<Diagnostic>>>,
+  diagnostics: Arc<RwLock<Vec<swc_common::errors::Diagnostic>>>,
 }
 
 impl ErrorBuffer {
@@ -16,6 +22,18 @@ impl ErrorBuffer {
       diagnostics: Arc::new(RwLock::new(Vec::new())),
     }
   }
+
+  /// Create a handler that emits diagnostics to the buffer.
+  pub fn handler(&self) -> Handler {
+    Handler::with_emitter_and_flags(
+      Box::new(self.clone()),
+      HandlerFlags {
+        can_emit_warnings: true,
+        dont_buffer_diagnostics: true,
+        ..HandlerFlags::default()
+      },
+    )
+  }
 }
 
 impl Emitter for ErrorBuffer {
@@ -24,37 +42,400 @@ impl Emitter for ErrorBuffer {
   }
 }
 
+/// The severity of a diagnostic.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+  Error,
+  Warning,
+}
+
+/// The offsets of a diagnostic in the source code, in UTF-16 code units like the JS string indexes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SpanOffsets {
+  pub start: u32,
+  pub end: u32,
+}
+
+/// A diagnostic message reported by the compiler.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+  pub message: String,
+  pub specifier: String,
+  pub severity: Severity,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub code: Option<String>,
+  /// 1-based line number.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub line: Option<usize>,
+  /// 0-based column number, in characters.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub column: Option<usize>,
+  /// The UTF-16 offsets of the span in the source code.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub span: Option<SpanOffsets>,
+  /// The surrounding source code with a caret under the offending span.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub code_frame: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match (self.line, self.column) {
+      (Some(line), Some(column)) => write!(fmt, "{} at {}:{}:{}", self.message, self.specifier, line, column),
+      _ => write!(fmt, "{}", self.message),
+    }
+  }
+}
+
+/// The error returned by the compiler.
+#[derive(Debug)]
+pub enum Error {
+  /// The module can't be parsed or transformed.
+  Diagnostics(Vec<Diagnostic>),
+  /// The options are invalid, e.g. a malformed import map.
+  InvalidOptions(String),
+  /// The CSS can't be compiled.
+  Css(String),
+  Other(anyhow::Error),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Diagnostics(diagnostics) => {
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        fmt.pad(&messages.join(","))
+      }
+      Error::InvalidOptions(message) | Error::Css(message) => fmt.pad(message),
+      Error::Other(err) => write!(fmt, "{}", err),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl From<anyhow::Error> for Error {
+  fn from(err: anyhow::Error) -> Self {
+    match err.downcast::<DiagnosticBuffer>() {
+      Ok(DiagnosticBuffer(diagnostics)) => Error::Diagnostics(diagnostics),
+      Err(err) => Error::Other(err),
+    }
+  }
+}
+
 /// A buffer for collecting diagnostic messages from the AST parser.
 #[derive(Debug)]
-pub struct DiagnosticBuffer(Vec<String>);
+pub struct DiagnosticBuffer(pub Vec<Diagnostic>);
 
 impl fmt::Display for DiagnosticBuffer {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-    fmt.pad(&self.0.join(","))
+    let messages: Vec<String> = self.0.iter().map(|d| d.to_string()).collect();
+    fmt.pad(&messages.join(","))
   }
 }
 
+impl std::error::Error for DiagnosticBuffer {}
+
 impl DiagnosticBuffer {
-  pub fn from_error_buffer<F>(error_buffer: ErrorBuffer, get_loc: F) -> Self
-  where
-    F: Fn(Span) -> Loc,
-  {
+  pub fn from_error_buffer(error_buffer: ErrorBuffer, source_map: &SourceMap) -> Self {
     let diagnostics = error_buffer.diagnostics.read().unwrap().clone();
     let diagnostics = diagnostics
       .iter()
       .map(|d| {
-        let mut message = d.message();
+        let mut diagnostic = Diagnostic {
+          message: d.message(),
+          specifier: error_buffer.specifier.clone(),
+          severity: match d.level {
+            Level::Warning | Level::Note | Level::Help => Severity::Warning,
+            _ => Severity::Error,
+          },
+          code: d.code.as_ref().map(|code| match code {
+            DiagnosticId::Error(code) | DiagnosticId::Lint(code) => code.clone(),
+          }),
+          line: None,
+          column: None,
+          span: None,
+          code_frame: None,
+        };
         if let Some(span) = d.span.primary_span() {
-          let loc = get_loc(span);
-          message = format!(
-            "{} at {}:{}:{}",
-            message, error_buffer.specifier, loc.line, loc.col_display
-          );
+          let loc = source_map.lookup_char_pos(span.lo);
+          diagnostic.line = Some(loc.line);
+          diagnostic.column = Some(loc.col.0);
+          diagnostic.span = Some(SpanOffsets {
+            start: get_utf16_offset(&loc.file, span.lo) as u32,
+            end: get_utf16_offset(&loc.file, span.hi) as u32,
+          });
+          diagnostic.code_frame = render_code_frame(source_map, span);
         }
-        message
+        diagnostic
       })
       .collect();
 
     Self(diagnostics)
   }
 }
+
+/// Renders a code frame for the span, like:
+///
+/// ```text
+///   1 | const a = 1;
+/// > 2 | const b = ;
+///     |           ^
+///   3 | console.log(a, b);
+/// ```
+pub fn render_code_frame(source_map: &SourceMap, span: Span) -> Option<String> {
+  let lo = source_map.lookup_char_pos(span.lo);
+  let hi = source_map.lookup_char_pos(span.hi);
+  let file = lo.file;
+  let first_line = lo.line.saturating_sub(CODE_FRAME_CONTEXT_LINES).max(1);
+  let last_line = (lo.line + CODE_FRAME_CONTEXT_LINES).min(file.count_lines());
+  let gutter_width = last_line.to_string().len();
+  let mut lines = Vec::new();
+
+  for line_number in first_line..=last_line {
+    let line = file.get_line(line_number - 1)?;
+    let line = line.trim_end();
+    let marker = if line_number == lo.line { '>' } else { ' ' };
+    lines.push(
+      format!("{} {:>w$} | {}", marker, line_number, line, w = gutter_width)
+        .trim_end()
+        .to_owned(),
+    );
+    if line_number == lo.line {
+      // keep tabs in the padding and count the wide chars twice so the caret lines up with the source
+      let padding: String = line
+        .chars()
+        .take(lo.col.0)
+        .map(|c| match c {
+          '\t' => "\t".to_owned(),
+          c => " ".repeat(c.width().unwrap_or_default()),
+        })
+        .collect();
+      let end = if hi.line == lo.line { hi.col.0 } else { usize::MAX };
+      let width: usize = line
+        .chars()
+        .skip(lo.col.0)
+        .take(end.saturating_sub(lo.col.0))
+        .map(|c| c.width().unwrap_or(1))
+        .sum();
+      lines.push(format!(
+        "  {:w$} | {}{}",
+        "",
+        padding,
+        "^".repeat(width.max(1)),
+        w = gutter_width
+      ));
+    }
+  }
+
+  Some(lines.join("\n"))
+}
+
+/// Get the offset of the position in the file, in UTF-16 code units.
+pub fn get_utf16_offset(file: &SourceFile, pos: BytePos) -> usize {
+  let mut offset = (pos.0 - file.start_pos.0) as usize;
+  // a multi-byte char takes 1 UTF-16 code unit, or 2 if it's encoded in 4 bytes
+  for c in file.multibyte_chars.iter().take_while(|c| c.pos < pos) {
+    offset -= c.bytes as usize - if c.bytes == 4 { 2 } else { 1 };
+  }
+  offset
+}
+
+/// Emit a warning to the `HANDLER` of the current transform, it's a no-op if no handler is set.
+pub fn emit_warning(span: Span, code: &str, message: &str) {
+  if HANDLER.is_set() {
+    HANDLER.with(|handler| {
+      handler
+        .struct_span_warn_with_code(span, message, DiagnosticId::Lint(code.into()))
+        .emit();
+    });
+  }
+}
+
+/// Get the diagnostic code of a syntax error, e.g. `ExpectedSemiForExprStmt`. The codes are a part of
+/// the public diagnostics, so they are mapped explicitly instead of using the `Debug` names of swc.
+pub fn syntax_error_code(kind: &SyntaxError) -> &'static str {
+  use SyntaxError::*;
+  match kind {
+    Eof => "Eof",
+    DeclNotAllowed => "DeclNotAllowed",
+    PrivateNameInInterface => "PrivateNameInInterface",
+    InvalidSuperCall => "InvalidSuperCall",
+    InvalidSuper => "InvalidSuper",
+    InvalidSuperPrivateName => "InvalidSuperPrivateName",
+    InvalidNewTarget => "InvalidNewTarget",
+    InvalidImport => "InvalidImport",
+    ArrowNotAllowed => "ArrowNotAllowed",
+    ExportNotAllowed => "ExportNotAllowed",
+    GetterSetterCannotBeReadonly => "GetterSetterCannotBeReadonly",
+    GetterParam => "GetterParam",
+    SetterParam => "SetterParam",
+    TopLevelAwait => "TopLevelAwait",
+    TopLevelAwaitInScript => "TopLevelAwaitInScript",
+    LegacyDecimal => "LegacyDecimal",
+    LegacyOctal => "LegacyOctal",
+    InvalidIdentChar => "InvalidIdentChar",
+    ExpectedDigit { .. } => "ExpectedDigit",
+    SetterParamRequired => "SetterParamRequired",
+    RestPatInSetter => "RestPatInSetter",
+    UnterminatedBlockComment => "UnterminatedBlockComment",
+    UnterminatedStrLit => "UnterminatedStrLit",
+    ExpectedUnicodeEscape => "ExpectedUnicodeEscape",
+    EscapeInReservedWord { .. } => "EscapeInReservedWord",
+    UnterminatedRegExp => "UnterminatedRegExp",
+    UnterminatedTpl => "UnterminatedTpl",
+    IdentAfterNum => "IdentAfterNum",
+    UnexpectedChar { .. } => "UnexpectedChar",
+    InvalidStrEscape => "InvalidStrEscape",
+    InvalidUnicodeEscape => "InvalidUnicodeEscape",
+    BadCharacterEscapeSequence { .. } => "BadCharacterEscapeSequence",
+    NumLitTerminatedWithExp => "NumLitTerminatedWithExp",
+    LegacyCommentInModule => "LegacyCommentInModule",
+    InvalidIdentInStrict(..) => "InvalidIdentInStrict",
+    InvalidIdentInAsync => "InvalidIdentInAsync",
+    EvalAndArgumentsInStrict => "EvalAndArgumentsInStrict",
+    ArgumentsInClassField => "ArgumentsInClassField",
+    IllegalLanguageModeDirective => "IllegalLanguageModeDirective",
+    UnaryInExp { .. } => "UnaryInExp",
+    Hash => "Hash",
+    LineBreakInThrow => "LineBreakInThrow",
+    LineBreakBeforeArrow => "LineBreakBeforeArrow",
+    Unexpected { .. } => "Unexpected",
+    UnexpectedTokenWithSuggestions { .. } => "UnexpectedTokenWithSuggestions",
+    ReservedWordInImport => "ReservedWordInImport",
+    AssignProperty => "AssignProperty",
+    Expected(..) => "Expected",
+    ExpectedSemiForExprStmt { .. } => "ExpectedSemiForExprStmt",
+    AwaitStar => "AwaitStar",
+    ReservedWordInObjShorthandOrPat => "ReservedWordInObjShorthandOrPat",
+    NullishCoalescingWithLogicalOp => "NullishCoalescingWithLogicalOp",
+    MultipleDefault { .. } => "MultipleDefault",
+    CommaAfterRestElement => "CommaAfterRestElement",
+    NonLastRestParam => "NonLastRestParam",
+    SpreadInParenExpr => "SpreadInParenExpr",
+    EmptyParenExpr => "EmptyParenExpr",
+    InvalidPat => "InvalidPat",
+    InvalidExpr => "InvalidExpr",
+    NotSimpleAssign => "NotSimpleAssign",
+    ExpectedIdent => "ExpectedIdent",
+    ExpectedSemi => "ExpectedSemi",
+    DuplicateLabel(..) => "DuplicateLabel",
+    AsyncGenerator => "AsyncGenerator",
+    NonTopLevelImportExport => "NonTopLevelImportExport",
+    ImportExportInScript => "ImportExportInScript",
+    ImportMetaInScript => "ImportMetaInScript",
+    PatVarWithoutInit => "PatVarWithoutInit",
+    WithInStrict => "WithInStrict",
+    ReturnNotAllowed => "ReturnNotAllowed",
+    TooManyVarInForInHead => "TooManyVarInForInHead",
+    VarInitializerInForInHead => "VarInitializerInForInHead",
+    LabelledGeneratorOrAsync => "LabelledGeneratorOrAsync",
+    LabelledFunctionInStrict => "LabelledFunctionInStrict",
+    YieldParamInGen => "YieldParamInGen",
+    AwaitParamInAsync => "AwaitParamInAsync",
+    AwaitForStmt => "AwaitForStmt",
+    AwaitInFunction => "AwaitInFunction",
+    UnterminatedJSXContents => "UnterminatedJSXContents",
+    EmptyJSXAttr => "EmptyJSXAttr",
+    InvalidJSXValue => "InvalidJSXValue",
+    JSXExpectedClosingTagForLtGt => "JSXExpectedClosingTagForLtGt",
+    JSXExpectedClosingTag { .. } => "JSXExpectedClosingTag",
+    InvalidLeadingDecorator => "InvalidLeadingDecorator",
+    DecoratorOnExport => "DecoratorOnExport",
+    TsRequiredAfterOptional => "TsRequiredAfterOptional",
+    TsInvalidParamPropPat => "TsInvalidParamPropPat",
+    SpaceBetweenHashAndIdent => "SpaceBetweenHashAndIdent",
+    AsyncConstructor => "AsyncConstructor",
+    PropertyNamedConstructor => "PropertyNamedConstructor",
+    PrivateConstructor => "PrivateConstructor",
+    PrivateNameModifier(..) => "PrivateNameModifier",
+    ConstructorAccessor => "ConstructorAccessor",
+    ReadOnlyMethod => "ReadOnlyMethod",
+    GeneratorConstructor => "GeneratorConstructor",
+    DuplicateConstructor => "DuplicateConstructor",
+    TsBindingPatCannotBeOptional => "TsBindingPatCannotBeOptional",
+    SuperCallOptional => "SuperCallOptional",
+    OptChainCannotFollowConstructorCall => "OptChainCannotFollowConstructorCall",
+    TrailingCommaInsideImport => "TrailingCommaInsideImport",
+    ExportDefaultWithOutFrom => "ExportDefaultWithOutFrom",
+    DotsWithoutIdentifier => "DotsWithoutIdentifier",
+    NumericSeparatorIsAllowedOnlyBetweenTwoDigits => "NumericSeparatorIsAllowedOnlyBetweenTwoDigits",
+    ImportBindingIsString(..) => "ImportBindingIsString",
+    ExportBindingIsString => "ExportBindingIsString",
+    ConstDeclarationsRequireInitialization => "ConstDeclarationsRequireInitialization",
+    DuplicatedRegExpFlags(..) => "DuplicatedRegExpFlags",
+    UnknownRegExpFlags => "UnknownRegExpFlags",
+    TS1003 => "TS1003",
+    TS1005 => "TS1005",
+    TS1009 => "TS1009",
+    TS1014 => "TS1014",
+    TS1015 => "TS1015",
+    TS1029(..) => "TS1029",
+    TS1030(..) => "TS1030",
+    TS1031 => "TS1031",
+    TS1038 => "TS1038",
+    TS1042 => "TS1042",
+    TS1047 => "TS1047",
+    TS1048 => "TS1048",
+    TS1056 => "TS1056",
+    TS1085 => "TS1085",
+    TS1089(..) => "TS1089",
+    TS1092 => "TS1092",
+    TS1096 => "TS1096",
+    TS1098 => "TS1098",
+    TS1100 => "TS1100",
+    TS1102 => "TS1102",
+    TS1105 => "TS1105",
+    TS1106 => "TS1106",
+    TS1107 => "TS1107",
+    TS1109 => "TS1109",
+    TS1110 => "TS1110",
+    TS1114 => "TS1114",
+    TS1115 => "TS1115",
+    TS1116 => "TS1116",
+    TS1123 => "TS1123",
+    TS1141 => "TS1141",
+    TS1162 => "TS1162",
+    TS1164 => "TS1164",
+    TS1171 => "TS1171",
+    TS1172 => "TS1172",
+    TS1173 => "TS1173",
+    TS1174 => "TS1174",
+    TS1175 => "TS1175",
+    TS1183 => "TS1183",
+    TS1184 => "TS1184",
+    TS1185 => "TS1185",
+    TS1093 => "TS1093",
+    TS1196 => "TS1196",
+    TS1242 => "TS1242",
+    TS1243(..) => "TS1243",
+    TS1244 => "TS1244",
+    TS1245 => "TS1245",
+    TS1267 => "TS1267",
+    TS1273(..) => "TS1273",
+    TS1274(..) => "TS1274",
+    TS1277(..) => "TS1277",
+    TS2206 => "TS2206",
+    TS2207 => "TS2207",
+    TS2369 => "TS2369",
+    TS2371 => "TS2371",
+    TS2406 => "TS2406",
+    TS2410 => "TS2410",
+    TS2414 => "TS2414",
+    TS2427 => "TS2427",
+    TS2452 => "TS2452",
+    TS2483 => "TS2483",
+    TS2491 => "TS2491",
+    TS2499 => "TS2499",
+    TS2703 => "TS2703",
+    TS4112 => "TS4112",
+    TSTypeAnnotationAfterAssign => "TSTypeAnnotationAfterAssign",
+    TsNonNullAssertionNotAllowed(..) => "TsNonNullAssertionNotAllowed",
+    WithLabel { .. } => "WithLabel",
+    ReservedTypeAssertion => "ReservedTypeAssertion",
+    ReservedArrowTypeParam => "ReservedArrowTypeParam",
+    _ => "SyntaxError",
+  }
+}
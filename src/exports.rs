@@ -0,0 +1,192 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use swc_ecmascript::ast::*;
+use swc_ecmascript::utils::find_pat_ids;
+
+/// The declaration kind of an export.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportKind {
+  Function,
+  Class,
+  Const,
+  Let,
+  Var,
+  Enum,
+  /// `export default <expr>` or an export that is not declared in the module.
+  Value,
+  /// `export { foo } from "./foo.ts"`, `export * from "./foo.ts"`
+  ReExport,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDescriptor {
+  /// the exported name, `default` for the default export and `*` for `export * from "..."`.
+  pub name: String,
+  pub kind: ExportKind,
+  #[serde(skip_serializing_if = "is_false")]
+  pub is_async: bool,
+  /// the source of the re-export.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source: Option<String>,
+}
+
+/// Analyze the exports of the module, type-only exports are ignored.
+pub fn analyze_exports(module: &Module) -> Vec<ExportDescriptor> {
+  get_exports(&module.body)
+}
+
+/// Get the exports of the module items, type-only exports are ignored.
+pub fn get_exports(items: &[ModuleItem]) -> Vec<ExportDescriptor> {
+  let decls = get_local_decls(items);
+  let mut exports = Vec::<ExportDescriptor>::new();
+  let mut push = |name: String, kind: ExportKind, is_async: bool, source: Option<String>| {
+    exports.push(ExportDescriptor {
+      name,
+      kind,
+      is_async,
+      source,
+    })
+  };
+
+  for item in items {
+    if let ModuleItem::ModuleDecl(decl) = item {
+      match decl {
+        // match: export function foo() {}
+        // match: export const foo = 1, bar = async () => {}
+        ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => {
+          for (name, kind, is_async) in get_decl_names(decl) {
+            push(name, kind, is_async, None);
+          }
+        }
+        // match: export default function App() {}
+        ModuleDecl::ExportDefaultDecl(ExportDefaultDecl { decl, .. }) => match decl {
+          DefaultDecl::Fn(FnExpr { function, .. }) => {
+            push("default".into(), ExportKind::Function, function.is_async, None)
+          }
+          DefaultDecl::Class(_) => push("default".into(), ExportKind::Class, false, None),
+          DefaultDecl::TsInterfaceDecl(_) => {}
+        },
+        // match: export default foo
+        // match: export default async () => {}
+        ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { expr, .. }) => {
+          let (kind, is_async) = match expr.as_ref() {
+            Expr::Ident(id) => decls
+              .get(id.sym.as_ref())
+              .copied()
+              .unwrap_or((ExportKind::Value, false)),
+            expr => (ExportKind::Value, is_async_fn_expr(expr)),
+          };
+          push("default".into(), kind, is_async, None);
+        }
+        // match: export { foo, bar as baz }
+        // match: export { foo, default as bar } from "./foo.ts"
+        // match: export * as foo from "./foo.ts"
+        ModuleDecl::ExportNamed(NamedExport {
+          specifiers,
+          src,
+          type_only: false,
+          ..
+        }) => {
+          let source = src.as_ref().map(|src| src.value.to_string());
+          for specifier in specifiers {
+            let (orig, exported) = match specifier {
+              ExportSpecifier::Named(ExportNamedSpecifier {
+                orig,
+                exported,
+                is_type_only: false,
+                ..
+              }) => (get_export_name(orig), exported.as_ref().map(get_export_name)),
+              ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => {
+                ("*".to_owned(), Some(get_export_name(name)))
+              }
+              ExportSpecifier::Default(ExportDefaultSpecifier { exported }) => {
+                ("default".to_owned(), Some(exported.sym.to_string()))
+              }
+              _ => continue,
+            };
+            let (kind, is_async) = if source.is_some() {
+              (ExportKind::ReExport, false)
+            } else {
+              decls.get(&orig).copied().unwrap_or((ExportKind::Value, false))
+            };
+            push(exported.unwrap_or(orig), kind, is_async, source.clone());
+          }
+        }
+        // match: export * from "./foo.ts"
+        ModuleDecl::ExportAll(ExportAll {
+          src, type_only: false, ..
+        }) => push("*".into(), ExportKind::ReExport, false, Some(src.value.to_string())),
+        _ => {}
+      }
+    }
+  }
+
+  exports
+}
+
+// get the top-level declarations of the module, used to find the kind of `export { foo }`.
+fn get_local_decls(items: &[ModuleItem]) -> HashMap<String, (ExportKind, bool)> {
+  let mut decls = HashMap::new();
+  for item in items {
+    if let ModuleItem::Stmt(Stmt::Decl(decl)) = item {
+      for (name, kind, is_async) in get_decl_names(decl) {
+        decls.insert(name, (kind, is_async));
+      }
+    }
+  }
+  decls
+}
+
+fn get_decl_names(decl: &Decl) -> Vec<(String, ExportKind, bool)> {
+  match decl {
+    Decl::Fn(FnDecl { ident, function, .. }) => {
+      vec![(ident.sym.to_string(), ExportKind::Function, function.is_async)]
+    }
+    Decl::Class(ClassDecl { ident, .. }) => vec![(ident.sym.to_string(), ExportKind::Class, false)],
+    Decl::Var(var) => {
+      let kind = match var.kind {
+        VarDeclKind::Const => ExportKind::Const,
+        VarDeclKind::Let => ExportKind::Let,
+        VarDeclKind::Var => ExportKind::Var,
+      };
+      let mut names = vec![];
+      for decl in &var.decls {
+        match &decl.name {
+          Pat::Ident(BindingIdent { id, .. }) => {
+            let is_async = decl.init.as_deref().map(is_async_fn_expr).unwrap_or_default();
+            names.push((id.sym.to_string(), kind, is_async));
+          }
+          pat => {
+            let ids: Vec<Id> = find_pat_ids(pat);
+            names.extend(ids.into_iter().map(|(sym, _)| (sym.to_string(), kind, false)));
+          }
+        }
+      }
+      names
+    }
+    Decl::TsEnum(decl) if !decl.declare => vec![(decl.id.sym.to_string(), ExportKind::Enum, false)],
+    _ => vec![],
+  }
+}
+
+fn is_async_fn_expr(expr: &Expr) -> bool {
+  match expr {
+    Expr::Arrow(ArrowExpr { is_async, .. }) => *is_async,
+    Expr::Fn(FnExpr { function, .. }) => function.is_async,
+    Expr::Paren(ParenExpr { expr, .. }) => is_async_fn_expr(expr),
+    _ => false,
+  }
+}
+
+fn get_export_name(name: &ModuleExportName) -> String {
+  match name {
+    ModuleExportName::Ident(id) => id.sym.to_string(),
+    ModuleExportName::Str(s) => s.value.to_string(),
+  }
+}
+
+fn is_false(value: &bool) -> bool {
+  !*value
+}
@@ -65,7 +65,7 @@ impl Fold for HmrFold {
     for item in &module_items {
       if let ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) = &item {
         if let Expr::Call(call) = expr.as_ref() {
-          if is_call_expr_by_name(&call, "$RefreshReg$") {
+          if is_call_expr_by_name(call, "$RefreshReg$") {
             react_refresh = true;
             break;
           }
@@ -0,0 +1,435 @@
+use crate::error::emit_warning;
+use crate::resolver::{is_http_url, Resolver};
+use crate::swc_helpers::{get_glob_parts, is_call_expr_by_name, is_import_meta_prop, join_glob_parts, new_str, pat_id};
+use path_slash::PathBufExt;
+use pathdiff::diff_paths;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::{cell::RefCell, rc::Rc};
+use swc_common::{Span, Spanned, DUMMY_SP};
+use swc_ecmascript::ast::*;
+use swc_ecmascript::utils::quote_ident;
+use swc_ecmascript::visit::{noop_fold_type, Fold, FoldWith};
+
+/// Expands the `import.meta.glob` calls into an object of the matched modules with the candidate
+/// files provided by the host, e.g. `import.meta.glob("./routes/*.tsx")` ->
+/// `{ "./routes/index.tsx": () => import("./routes/index.tsx") }`. With `{ eager: true }` the modules
+/// are imported statically, `{ "./routes/index.tsx": __glob_0_0 }`.
+pub fn import_glob(resolver: Rc<RefCell<Resolver>>) -> impl Fold {
+  ImportGlobFold {
+    resolver,
+    imports: vec![],
+    count: 0,
+  }
+}
+
+pub struct ImportGlobFold {
+  resolver: Rc<RefCell<Resolver>>,
+  imports: Vec<ModuleItem>,
+  count: usize,
+}
+
+struct GlobCall {
+  patterns: Vec<String>,
+  eager: bool,
+  span: Span,
+}
+
+impl ImportGlobFold {
+  fn expand(&mut self, glob: GlobCall) -> Option<Expr> {
+    let resolver = self.resolver.borrow();
+    let files = match &resolver.glob_files {
+      Some(files) => files,
+      None => {
+        emit_warning(
+          glob.span,
+          "unresolved-import-glob",
+          "Could not expand `import.meta.glob`: no glob files are provided",
+        );
+        return None;
+      }
+    };
+    if is_http_url(&resolver.specifier) {
+      emit_warning(
+        glob.span,
+        "unresolved-import-glob",
+        "Could not expand `import.meta.glob` in a remote module",
+      );
+      return None;
+    }
+    // the patterns are checked by `get_glob_call`
+    let matched = match_files(&resolver.specifier, files, &glob.patterns)?;
+
+    let index = self.count;
+    self.count += 1;
+    let props = matched
+      .iter()
+      .enumerate()
+      .map(|(i, key)| {
+        let value = if glob.eager {
+          let local = quote_ident!(format!("__glob_{}_{}", index, i));
+          self.imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+            span: glob.span,
+            specifiers: vec![ImportSpecifier::Namespace(ImportStarAsSpecifier {
+              span: DUMMY_SP,
+              local: local.clone(),
+            })],
+            src: Box::new(Str {
+              span: glob.span,
+              ..new_str(key)
+            }),
+            type_only: false,
+            asserts: None,
+          })));
+          Expr::Ident(local)
+        } else {
+          lazy_import(key, glob.span)
+        };
+        (key.clone(), value)
+      })
+      .collect();
+    Some(new_object(props, glob.span))
+  }
+
+  // rewrite `import(`./locales/${lang}.ts`)` to a lookup of the matched modules, the unmatched
+  // specifiers are imported as they are, the map has no prototype to not match `Object.prototype` keys:
+  // `((m, k) => m[k] ? m[k]() : import(k))({ __proto__: null, "./locales/en.ts": () => import("./locales/en.ts") }, `./locales/${lang}.ts`)`
+  fn expand_dynamic_import(&self, call: &CallExpr) -> Option<Expr> {
+    if !is_call_expr_by_name(call, "import") || call.args.len() != 1 {
+      return None;
+    }
+    let arg = &call.args[0].expr;
+    let parts = get_glob_parts(arg)?;
+    if !(parts[0].starts_with("./") || parts[0].starts_with("../") || parts[0].starts_with('/')) {
+      return None;
+    }
+    let resolver = self.resolver.borrow();
+    let files = resolver.glob_files.as_ref()?;
+    if is_http_url(&resolver.specifier) {
+      return None;
+    }
+    let span = arg.span();
+    let props = match_files(&resolver.specifier, files, &[join_glob_parts(&parts)])?
+      .into_iter()
+      .map(|key| {
+        let value = lazy_import(&key, span);
+        (key, value)
+      })
+      .collect();
+    let m = || Box::new(Expr::Ident(quote_ident!("m")));
+    let k = || Box::new(Expr::Ident(quote_ident!("k")));
+    let lookup = || {
+      Box::new(Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: m(),
+        prop: MemberProp::Computed(ComputedPropName {
+          span: DUMMY_SP,
+          expr: k(),
+        }),
+      }))
+    };
+    let lookup_fn = Expr::Arrow(ArrowExpr {
+      span: DUMMY_SP,
+      params: vec![pat_id("m"), pat_id("k")],
+      body: BlockStmtOrExpr::Expr(Box::new(Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: lookup(),
+        cons: Box::new(Expr::Call(CallExpr {
+          span: DUMMY_SP,
+          callee: Callee::Expr(lookup()),
+          args: vec![],
+          type_args: None,
+        })),
+        alt: Box::new(Expr::Call(CallExpr {
+          span: DUMMY_SP,
+          callee: Callee::Import(Import { span: DUMMY_SP }),
+          args: vec![ExprOrSpread {
+            spread: None,
+            expr: k(),
+          }],
+          type_args: None,
+        })),
+      }))),
+      is_async: false,
+      is_generator: false,
+      type_params: None,
+      return_type: None,
+    });
+    Some(Expr::Call(CallExpr {
+      span: call.span,
+      callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr: Box::new(lookup_fn),
+      }))),
+      args: vec![
+        ExprOrSpread {
+          spread: None,
+          expr: Box::new(new_null_proto_object(props, span)),
+        },
+        call.args[0].clone(),
+      ],
+      type_args: None,
+    }))
+  }
+}
+
+// match the candidate files with the glob patterns, `!` patterns exclude the files. The returned keys
+// are relative to the module unless the pattern is absolute. Returns `None` if a pattern is invalid.
+fn match_files(specifier: &str, files: &[String], patterns: &[String]) -> Option<Vec<String>> {
+  let specifier = to_root_path(specifier);
+  let dir = match Path::new(&specifier).parent() {
+    Some(dir) => dir.to_owned(),
+    None => PathBuf::from("."),
+  };
+  let mut includes = vec![];
+  let mut excludes = vec![];
+  for pattern in patterns {
+    let (pattern, negative) = match pattern.strip_prefix('!') {
+      Some(pattern) => (pattern, true),
+      None => (pattern.as_str(), false),
+    };
+    let re = glob_to_regex(&resolve_pattern(&dir, pattern))?;
+    if negative {
+      excludes.push(re);
+    } else {
+      includes.push((re, pattern.starts_with('/')));
+    }
+  }
+
+  let mut matched: Vec<String> = vec![];
+  let mut files: Vec<String> = files.iter().map(|file| to_root_path(file)).collect();
+  files.sort();
+  files.dedup();
+  for file in files {
+    if file == specifier || excludes.iter().any(|re| re.is_match(&file)) {
+      continue;
+    }
+    if let Some((_, absolute)) = includes.iter().find(|(re, _)| re.is_match(&file)) {
+      // the keys are relative to the module unless the pattern is absolute
+      let key = if *absolute {
+        file.trim_start_matches('.').to_owned()
+      } else {
+        match diff_paths(&file, &dir) {
+          Some(path) => {
+            let path = path.to_slash_lossy().to_string();
+            if path.starts_with("../") {
+              path
+            } else {
+              "./".to_owned() + &path
+            }
+          }
+          // fallback to the root path if the file is not reachable from the module dir
+          None => file.trim_start_matches('.').to_owned(),
+        }
+      };
+      matched.push(key);
+    }
+  }
+
+  Some(matched)
+}
+
+// () => import("./routes/index.tsx")
+fn lazy_import(key: &str, span: Span) -> Expr {
+  Expr::Arrow(ArrowExpr {
+    span: DUMMY_SP,
+    params: vec![],
+    body: BlockStmtOrExpr::Expr(Box::new(Expr::Call(CallExpr {
+      span: DUMMY_SP,
+      callee: Callee::Import(Import { span: DUMMY_SP }),
+      args: vec![ExprOrSpread {
+        spread: None,
+        expr: Box::new(Expr::Lit(Lit::Str(Str { span, ..new_str(key) }))),
+      }],
+      type_args: None,
+    }))),
+    is_async: false,
+    is_generator: false,
+    type_params: None,
+    return_type: None,
+  })
+}
+
+fn new_object(props: Vec<(String, Expr)>, span: Span) -> Expr {
+  Expr::Object(ObjectLit {
+    span,
+    props: props
+      .into_iter()
+      .map(|(key, value)| {
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+          key: PropName::Str(new_str(&key)),
+          value: Box::new(value),
+        })))
+      })
+      .collect(),
+  })
+}
+
+// `{ __proto__: null, ...props }`
+fn new_null_proto_object(props: Vec<(String, Expr)>, span: Span) -> Expr {
+  let mut object = new_object(props, span);
+  if let Expr::Object(ObjectLit { props, .. }) = &mut object {
+    props.insert(
+      0,
+      PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(quote_ident!("__proto__")),
+        value: Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))),
+      }))),
+    );
+  }
+  object
+}
+
+impl Fold for ImportGlobFold {
+  noop_fold_type!();
+
+  fn fold_module(&mut self, module: Module) -> Module {
+    let mut module = module.fold_children_with(self);
+    if !self.imports.is_empty() {
+      let imports = std::mem::take(&mut self.imports);
+      module.body.splice(0..0, imports);
+    }
+    module
+  }
+
+  fn fold_expr(&mut self, expr: Expr) -> Expr {
+    if let Expr::Call(call) = &expr {
+      if let Some(glob) = get_glob_call(call) {
+        if let Some(expr) = self.expand(glob) {
+          return expr;
+        }
+      } else if let Some(expr) = self.expand_dynamic_import(call) {
+        return expr.fold_children_with(self);
+      }
+    }
+    expr.fold_children_with(self)
+  }
+}
+
+// match: import.meta.glob("./routes/*.tsx")
+// match: import.meta.glob(["./routes/*.tsx", "!./routes/_*.tsx"], { eager: true })
+fn get_glob_call(call: &CallExpr) -> Option<GlobCall> {
+  match &call.callee {
+    Callee::Expr(callee) if is_import_meta_prop(callee, "glob") => {}
+    _ => return None,
+  }
+  let invalid = || {
+    emit_warning(
+      call.span,
+      "invalid-import-glob",
+      "The arguments of `import.meta.glob` must be string literals and an object literal of options",
+    );
+    None
+  };
+  let patterns = match call.args.first().map(|arg| arg.expr.as_ref()) {
+    Some(Expr::Lit(Lit::Str(s))) => vec![s.value.to_string()],
+    Some(Expr::Array(ArrayLit { elems, .. })) => {
+      let mut patterns = vec![];
+      for elem in elems {
+        match elem.as_ref().map(|elem| elem.expr.as_ref()) {
+          Some(Expr::Lit(Lit::Str(s))) => patterns.push(s.value.to_string()),
+          _ => return invalid(),
+        }
+      }
+      patterns
+    }
+    _ => return invalid(),
+  };
+  let mut eager = false;
+  match call.args.get(1).map(|arg| arg.expr.as_ref()) {
+    Some(Expr::Object(ObjectLit { props, .. })) => {
+      for prop in props {
+        match prop {
+          PropOrSpread::Prop(prop) => match prop.as_ref() {
+            Prop::KeyValue(KeyValueProp {
+              key: PropName::Ident(key),
+              value,
+            }) if key.sym.as_ref() == "eager" => match value.as_ref() {
+              Expr::Lit(Lit::Bool(Bool { value, .. })) => eager = *value,
+              _ => return invalid(),
+            },
+            _ => return invalid(),
+          },
+          _ => return invalid(),
+        }
+      }
+    }
+    Some(_) => return invalid(),
+    None => {}
+  }
+  if let Some(pattern) = patterns
+    .iter()
+    .find(|pattern| glob_to_regex(pattern.trim_start_matches('!')).is_none())
+  {
+    emit_warning(
+      call.span,
+      "invalid-import-glob",
+      &format!("Invalid glob pattern \"{}\" of `import.meta.glob`", pattern),
+    );
+    return None;
+  }
+  Some(GlobCall {
+    patterns,
+    eager,
+    span: call.span,
+  })
+}
+
+// normalize the path to the root path form `./routes/index.tsx`.
+fn to_root_path(path: &str) -> String {
+  let mut segments: Vec<&str> = vec![];
+  for segment in path.split('/') {
+    match segment {
+      "" | "." => {}
+      ".." => {
+        segments.pop();
+      }
+      _ => segments.push(segment),
+    }
+  }
+  "./".to_owned() + &segments.join("/")
+}
+
+// resolve the pattern with the module dir, `/` patterns are relative to the root.
+fn resolve_pattern(dir: &Path, pattern: &str) -> String {
+  if pattern.starts_with('/') {
+    to_root_path(pattern)
+  } else {
+    to_root_path(&dir.join(pattern).to_slash().unwrap())
+  }
+}
+
+// convert the glob pattern to regex, supports `**`, `*`, `?` and `{a,b}`. Returns `None` if the pattern
+// is invalid, e.g. an unclosed `{`.
+fn glob_to_regex(pattern: &str) -> Option<Regex> {
+  let mut re = String::from("^");
+  let mut chars = pattern.chars().peekable();
+  let mut in_group = false;
+  while let Some(c) = chars.next() {
+    match c {
+      '*' if chars.peek() == Some(&'*') => {
+        chars.next();
+        if chars.peek() == Some(&'/') {
+          chars.next();
+          re.push_str("(?:[^/]+/)*");
+        } else {
+          re.push_str(".*");
+        }
+      }
+      '*' => re.push_str("[^/]*"),
+      '?' => re.push_str("[^/]"),
+      '{' if !in_group => {
+        in_group = true;
+        re.push_str("(?:");
+      }
+      '}' if in_group => {
+        in_group = false;
+        re.push(')');
+      }
+      ',' if in_group => re.push('|'),
+      c => re.push_str(&regex::escape(&c.to_string())),
+    }
+  }
+  re.push('$');
+  Regex::new(&re).ok()
+}
@@ -0,0 +1,59 @@
+use crate::resolve_fold::get_import_names;
+use crate::resolver::{DependencyKind, Resolver};
+use crate::swc_helpers::new_str;
+use std::{cell::RefCell, rc::Rc};
+use swc_ecmascript::ast::*;
+use swc_ecmascript::visit::{noop_fold_type, Fold};
+
+/// Resolves the runtime imports injected by the `react::jsx` transform, e.g.
+/// `import { jsx as _jsx } from "react/jsx-runtime"`.
+pub fn jsx_runtime(resolver: Rc<RefCell<Resolver>>, import_source: String) -> impl Fold {
+  JsxRuntimeFold {
+    resolver,
+    import_source,
+  }
+}
+
+pub struct JsxRuntimeFold {
+  resolver: Rc<RefCell<Resolver>>,
+  import_source: String,
+}
+
+impl Fold for JsxRuntimeFold {
+  noop_fold_type!();
+
+  fn fold_module_items(&mut self, module_items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+    let mut resolver = self.resolver.borrow_mut();
+    module_items
+      .into_iter()
+      .map(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) if is_jsx_runtime_import(&decl) => {
+          // swc always imports `createElement` from "react", use the configured import source instead
+          let src = match decl.src.value.as_ref() {
+            "react" => self.import_source.as_str(),
+            src => src,
+          };
+          let import_url = resolver.resolve(src, DependencyKind::Import, None);
+          if let Some(dep) = resolver.deps.last_mut() {
+            dep.names = get_import_names(&decl.specifiers);
+          }
+          ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+            src: Box::new(new_str(&import_url)),
+            ..decl
+          }))
+        }
+        _ => item,
+      })
+      .collect()
+  }
+}
+
+// the injected imports: `import { jsx as _jsx } from "react/jsx-runtime"` and
+// `import { createElement as _createElement } from "react"` for the elements with both spread props and key.
+fn is_jsx_runtime_import(decl: &ImportDecl) -> bool {
+  if !decl.span.is_dummy() {
+    return false;
+  }
+  let src = decl.src.value.as_ref();
+  src.ends_with("/jsx-runtime") || src.ends_with("/jsx-dev-runtime") || src == "react"
+}
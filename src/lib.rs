@@ -1,36 +1,72 @@
+mod asset;
+#[cfg(feature = "parallel")]
+mod batch;
+mod cache;
 mod css;
+mod define;
 mod error;
+mod exports;
 mod hmr;
+mod import_glob;
+mod jsx_runtime;
 mod minifier;
+mod prune_deps;
 mod resolve_fold;
 mod resolver;
+mod rsc;
+mod scanner;
+mod strip_data_export;
 mod swc;
 mod swc_helpers;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 #[cfg(test)]
 mod tests;
 
-use minifier::MinifierOptions;
-use resolver::{DependencyDescriptor, Resolver};
+pub use cache::TransformCache;
+pub use css::{
+  Config as CssConfig, CssModulesConfig, CssModulesOption, DependencyOptions as CssDependencyOptions, Drafts,
+  OwnedPseudoClasses, TransformResult as CssTransformResult,
+};
+pub use error::{Diagnostic, Error, Severity, SpanOffsets};
+pub use exports::{ExportDescriptor, ExportKind};
+pub use lightningcss::targets::Browsers;
+pub use minifier::MinifierOptions;
+pub use resolver::{DependencyDescriptor, DependencyKind, GlobPattern, Location, Position, ResolveHook};
+pub use rsc::{Directive, RscMode};
+pub use strip_data_export::StripDataExportOption;
+
+#[cfg(feature = "parallel")]
+pub use batch::transform_batch;
+
+use resolver::{ParsedImportMap, Resolver};
+use rsc::RscConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::str::FromStr;
+use std::sync::Arc;
 use std::{cell::RefCell, rc::Rc};
+use strip_data_export::StripDataExport;
 use swc::{EmitOptions, SWC};
 use swc_ecmascript::ast::EsVersion;
-use url::Url;
-use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
 
-#[derive(Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Options {
   pub aleph_pkg_uri: Option<String>,
   pub lang: Option<String>,
   pub target: Option<String>,
   pub import_map: Option<String>,
+  pub import_map_base_url: Option<String>,
+  pub cdn_origin: Option<String>,
   pub global_version: Option<String>,
   pub graph_versions: Option<HashMap<String, String>>,
-  pub strip_data_export: Option<bool>,
+  pub strip_data_export: Option<StripDataExportOption>,
+  pub rsc: Option<RscMode>,
+  pub rsc_runtime: Option<String>,
+  pub define: Option<HashMap<String, serde_json::Value>>,
+  pub glob_files: Option<Vec<String>>,
+  pub asset_inline_limit: Option<usize>,
   pub resolve_remote_module: Option<bool>,
   pub is_dev: Option<bool>,
   pub source_map: Option<bool>,
@@ -40,67 +76,176 @@ pub struct Options {
   pub jsx_import_source: Option<String>,
   pub react_refresh: Option<bool>,
   pub minify: Option<MinifierOptions>,
+  pub error_recovery: Option<bool>,
+  pub lexer_only: Option<bool>,
+  pub type_only_deps: Option<bool>,
+  #[serde(skip)]
+  pub resolve_hook: Option<Arc<dyn ResolveHook>>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransformOutput {
   pub code: String,
 
-  #[serde(skip_serializing_if = "Vec::is_empty")]
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
   pub deps: Vec<DependencyDescriptor>,
 
-  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(default, skip_serializing_if = "Option::is_none")]
   pub map: Option<String>,
-}
 
-#[wasm_bindgen(js_name = "parseDeps")]
-pub fn parse_deps(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
-  console_error_panic_hook::set_once();
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub directives: Vec<Directive>,
 
-  let options: Options = serde_wasm_bindgen::from_value(options).unwrap();
-  let importmap = import_map::parse_from_json(
-    &Url::from_str("file:///").unwrap(),
-    options.import_map.unwrap_or("{}".into()).as_str(),
-  )
-  .expect("could not pause the import map")
-  .import_map;
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<Diagnostic>,
+
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub errors: Vec<Diagnostic>,
+}
+
+/// Parse the dependencies of the module.
+pub fn parse_deps(specifier: &str, code: &str, options: &Options) -> Result<Vec<DependencyDescriptor>, Error> {
+  let importmap = parse_import_map(options)?;
   let resolver = Rc::new(RefCell::new(Resolver::new(
     specifier,
     "",
-    importmap,
+    importmap.import_map,
     HashMap::new(),
     None,
     false,
     false,
   )));
-  let module = SWC::parse(specifier, code, EsVersion::Es2022, options.lang).expect("could not parse the module");
-  let deps = module.parse_deps(resolver).expect("could not parse the module");
+  resolver.borrow_mut().hook = options.resolve_hook.clone();
+  resolver.borrow_mut().integrity = importmap.integrity;
+  resolver.borrow_mut().cdn_origin = options.cdn_origin.clone();
+  resolver.borrow_mut().glob_files = options.glob_files.clone();
+  let type_only_deps = options.type_only_deps.unwrap_or_default();
+  if options.lexer_only.unwrap_or_default() {
+    return Ok(scanner::scan_deps(
+      specifier,
+      code,
+      options.lang.clone(),
+      resolver,
+      type_only_deps,
+    )?);
+  }
+  let module = SWC::parse(
+    specifier,
+    code,
+    EsVersion::Es2022,
+    options.lang.clone(),
+    options.error_recovery.unwrap_or_default(),
+  )?;
+  Ok(module.parse_deps(resolver, type_only_deps)?)
+}
 
-  Ok(serde_wasm_bindgen::to_value(&deps).unwrap())
+/// Analyze the exports of the module.
+pub fn analyze_exports(specifier: &str, code: &str, options: &Options) -> Result<Vec<ExportDescriptor>, Error> {
+  let module = SWC::parse(
+    specifier,
+    code,
+    EsVersion::Es2022,
+    options.lang.clone(),
+    options.error_recovery.unwrap_or_default(),
+  )?;
+  Ok(exports::analyze_exports(&module.module))
 }
 
-#[wasm_bindgen(js_name = "transform")]
-pub fn transform(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
-  console_error_panic_hook::set_once();
+/// Transform the JS/TS/JSX/TSX module into JS, JSON and text modules are transformed into ES modules.
+pub fn transform(specifier: &str, code: &str, options: &Options) -> Result<TransformOutput, Error> {
+  transform_prepared(specifier, code, options, &PreparedOptions::new(options)?)
+}
 
-  let options: Options = serde_wasm_bindgen::from_value(options).unwrap();
-  let importmap = import_map::parse_from_json(
-    &Url::from_str("file:///").unwrap(),
-    options.import_map.unwrap_or("{}".into()).as_str(),
+/// Transform the module from the raw bytes, JSON, text and binary assets are transformed into ES modules,
+/// e.g. `export default "data:image/png;base64,..."`, the other modules must be UTF-8 encoded.
+pub fn transform_asset(specifier: &str, data: &[u8], options: &Options) -> Result<TransformOutput, Error> {
+  match asset::get_asset_kind(specifier, options.lang.as_deref()) {
+    Some(kind) => transform_asset_kind(specifier, data, kind, options),
+    None => {
+      let code = std::str::from_utf8(data)
+        .map_err(|err| Error::Other(anyhow::anyhow!("Invalid UTF-8 in \"{}\": {}", specifier, err)))?;
+      transform(specifier, code, options)
+    }
+  }
+}
+
+fn transform_asset_kind(
+  specifier: &str,
+  data: &[u8],
+  kind: asset::AssetKind,
+  options: &Options,
+) -> Result<TransformOutput, Error> {
+  asset::transform(
+    specifier,
+    data,
+    kind,
+    options.asset_inline_limit.unwrap_or(asset::DEFAULT_INLINE_LIMIT),
+    options.minify.is_some(),
   )
-  .expect("could not pause the import map")
-  .import_map;
+}
+
+/// The options parsed once and shared by the transforms of the modules.
+struct PreparedOptions {
+  import_map: ParsedImportMap,
+  graph_versions: Arc<HashMap<String, String>>,
+  strip_data_export: Option<StripDataExport>,
+  rsc: Option<RscConfig>,
+}
+
+impl PreparedOptions {
+  fn new(options: &Options) -> Result<Self, Error> {
+    let strip_data_export = match &options.strip_data_export {
+      Some(option) => option
+        .clone()
+        .into_config()
+        .map_err(|err| Error::InvalidOptions(err.to_string()))?,
+      None => None,
+    };
+    let rsc = match (options.rsc, &options.rsc_runtime) {
+      (Some(mode), Some(runtime)) => Some(RscConfig {
+        mode,
+        runtime: runtime.clone(),
+      }),
+      (Some(_), None) => {
+        return Err(Error::InvalidOptions(
+          "The `rscRuntime` option is required by the `rsc` option".into(),
+        ))
+      }
+      (None, _) => None,
+    };
+    Ok(PreparedOptions {
+      import_map: parse_import_map(options)?,
+      graph_versions: Arc::new(options.graph_versions.clone().unwrap_or_default()),
+      strip_data_export,
+      rsc,
+    })
+  }
+}
+
+fn transform_prepared(
+  specifier: &str,
+  code: &str,
+  options: &Options,
+  prepared: &PreparedOptions,
+) -> Result<TransformOutput, Error> {
+  if let Some(kind) = asset::get_asset_kind(specifier, options.lang.as_deref()) {
+    return transform_asset_kind(specifier, code.as_bytes(), kind, options);
+  }
   let resolver = Rc::new(RefCell::new(Resolver::new(
     specifier,
-    &options.aleph_pkg_uri.unwrap_or("https://deno.land/x/aleph".into()),
-    importmap,
-    options.graph_versions.unwrap_or_default(),
-    options.global_version,
+    options.aleph_pkg_uri.as_deref().unwrap_or("https://deno.land/x/aleph"),
+    prepared.import_map.import_map.clone(),
+    prepared.graph_versions.clone(),
+    options.global_version.clone(),
     options.resolve_remote_module.unwrap_or_default(),
     options.is_dev.unwrap_or_default(),
   )));
-  let target = match options.target.unwrap_or_default().as_str() {
+  resolver.borrow_mut().hook = options.resolve_hook.clone();
+  resolver.borrow_mut().integrity = prepared.import_map.integrity.clone();
+  resolver.borrow_mut().cdn_origin = options.cdn_origin.clone();
+  resolver.borrow_mut().glob_files = options.glob_files.clone();
+  let target = match options.target.as_deref().unwrap_or_default() {
     "es2015" => EsVersion::Es2015,
     "es2016" => EsVersion::Es2016,
     "es2017" => EsVersion::Es2017,
@@ -111,38 +256,78 @@ pub fn transform(specifier: &str, code: &str, options: JsValue) -> Result<JsValu
     "es2022" => EsVersion::Es2022,
     _ => EsVersion::Es2022, // use latest version
   };
-  let module = SWC::parse(specifier, code, target, options.lang).expect("could not parse the module");
-  let (code, map) = module
-    .transform(
-      resolver.clone(),
-      &EmitOptions {
-        target,
-        jsx: options.jsx,
-        jsx_pragma: options.jsx_pragma,
-        jsx_pragma_frag: options.jsx_pragma_frag,
-        jsx_import_source: options.jsx_import_source,
-        react_refresh: options.react_refresh.unwrap_or_default(),
-        strip_data_export: options.strip_data_export.unwrap_or_default(),
-        minify: options.minify,
-        source_map: options.source_map.unwrap_or_default(),
-      },
-    )
-    .expect("could not transform the module");
+  let module = SWC::parse(
+    specifier,
+    code,
+    target,
+    options.lang.clone(),
+    options.error_recovery.unwrap_or_default(),
+  )?;
+  let errors = module.errors.clone();
+  let parse_warnings = module.warnings.clone();
+  let directives = module.directives();
+  let (code, map, warnings) = module.transform(
+    resolver.clone(),
+    &EmitOptions {
+      target,
+      jsx: options.jsx.clone(),
+      jsx_pragma: options.jsx_pragma.clone(),
+      jsx_pragma_frag: options.jsx_pragma_frag.clone(),
+      jsx_import_source: options.jsx_import_source.clone(),
+      react_refresh: options.react_refresh.unwrap_or_default(),
+      strip_data_export: prepared.strip_data_export.clone(),
+      rsc: prepared.rsc.clone(),
+      define: options.define.clone().unwrap_or_default(),
+      minify: options.minify,
+      source_map: options.source_map.unwrap_or_default(),
+    },
+  )?;
+  let warnings = [
+    import_map_warnings(specifier, &prepared.import_map),
+    parse_warnings,
+    warnings,
+  ]
+  .concat();
   let r = resolver.borrow();
 
-  Ok(
-    serde_wasm_bindgen::to_value(&TransformOutput {
-      code,
-      deps: r.deps.clone(),
-      map,
-    })
-    .unwrap(),
+  Ok(TransformOutput {
+    code,
+    deps: r.deps.clone(),
+    map,
+    directives,
+    warnings,
+    errors,
+  })
+}
+
+/// Compile the CSS with lightningcss.
+pub fn compile_css(filename: &str, code: &str, config: &CssConfig) -> Result<CssTransformResult, Error> {
+  Ok(css::compile(filename.into(), code, config)?)
+}
+
+/// Parse the import map of the options, the base URL is `file:///` by default.
+fn parse_import_map(options: &Options) -> Result<ParsedImportMap, Error> {
+  resolver::parse_import_map(
+    options.import_map.as_deref().unwrap_or("{}"),
+    options.import_map_base_url.as_deref().unwrap_or("file:///"),
   )
+  .map_err(|err| Error::InvalidOptions(err.to_string()))
 }
 
-#[wasm_bindgen(js_name = "parcelCSS")]
-pub fn parcel_css(filename: &str, code: &str, config_raw: JsValue) -> Result<JsValue, JsValue> {
-  let config: css::Config = serde_wasm_bindgen::from_value(config_raw).unwrap();
-  let res = css::compile(filename.into(), code, &config)?;
-  Ok(serde_wasm_bindgen::to_value(&res).unwrap())
+/// The issues found in the import map are reported as warnings of the module.
+fn import_map_warnings(specifier: &str, import_map: &ParsedImportMap) -> Vec<Diagnostic> {
+  import_map
+    .diagnostics
+    .iter()
+    .map(|message| Diagnostic {
+      message: message.clone(),
+      specifier: specifier.into(),
+      severity: Severity::Warning,
+      code: Some("import-map".into()),
+      line: None,
+      column: None,
+      span: None,
+      code_frame: None,
+    })
+    .collect()
 }
@@ -0,0 +1,566 @@
+use aleph_compiler::{CssConfig, CssModulesOption, Diagnostic, Error, MinifierOptions, Options, Severity};
+use path_slash::PathExt;
+use pathdiff::diff_paths;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+use url::Url;
+
+const USAGE: &str = "Usage: aleph-compiler <COMMAND> [OPTIONS] <PATH>...
+
+Commands:
+  transform   Transform JS/TS/JSX/TSX modules into JS
+  parse-deps  Print the dependencies of the modules as JSON
+  css         Compile CSS files
+
+Options:
+  -c, --config <FILE>   The JSON config of the transform options, or a `deno.json`
+                        [default: ./deno.json if it exists]
+  -o, --out-dir <DIR>   The directory to write the outputs, print to stdout if not specified
+      --minify          Minify the outputs
+      --source-map      Write the source maps as `.map` files
+      --dev             Transform in development mode
+  -h, --help            Print help
+  -V, --version         Print version";
+
+/// The extensions of the modules to transform in a directory.
+const MODULE_EXTS: [&str; 5] = ["js", "jsx", "ts", "tsx", "mts"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+  Transform,
+  ParseDeps,
+  Css,
+}
+
+struct Args {
+  command: Command,
+  /// The working directory, the relative paths of the arguments are resolved against it.
+  cwd: PathBuf,
+  inputs: Vec<PathBuf>,
+  config: Option<PathBuf>,
+  out_dir: Option<PathBuf>,
+  minify: bool,
+  source_map: bool,
+  dev: bool,
+}
+
+fn main() {
+  let args = match parse_args(env::args().skip(1).collect()) {
+    Ok(args) => args,
+    Err(message) => {
+      eprintln!("error: {}\n\n{}", message, USAGE);
+      process::exit(2);
+    }
+  };
+  let ok = match args.command {
+    Command::Transform => load_options(&args).map(|options| transform(&args, &options)),
+    Command::ParseDeps => load_options(&args).map(|options| parse_deps(&args, &options)),
+    Command::Css => Ok(compile_css(&args)),
+  };
+  match ok {
+    Ok(true) => {}
+    Ok(false) => process::exit(1),
+    Err(message) => {
+      eprintln!("error: {}", message);
+      process::exit(1);
+    }
+  }
+}
+
+fn parse_args(argv: Vec<String>) -> Result<Args, String> {
+  let mut argv = argv.into_iter();
+  let command = match argv.next().as_deref() {
+    Some("transform") => Command::Transform,
+    Some("parse-deps") => Command::ParseDeps,
+    Some("css") => Command::Css,
+    Some("-h" | "--help") => {
+      println!("{}", USAGE);
+      process::exit(0);
+    }
+    Some("-V" | "--version") => {
+      println!("aleph-compiler {}", env!("CARGO_PKG_VERSION"));
+      process::exit(0);
+    }
+    Some(command) => return Err(format!("unknown command '{}'", command)),
+    None => return Err("missing command".into()),
+  };
+  let mut args = Args {
+    command,
+    cwd: env::current_dir().map_err(|err| format!("could not get the working directory: {}", err))?,
+    inputs: vec![],
+    config: None,
+    out_dir: None,
+    minify: false,
+    source_map: false,
+    dev: false,
+  };
+  while let Some(arg) = argv.next() {
+    match arg.as_str() {
+      "-c" | "--config" => args.config = Some(argv.next().ok_or("missing value of --config")?.into()),
+      "-o" | "--out-dir" => args.out_dir = Some(argv.next().ok_or("missing value of --out-dir")?.into()),
+      "--minify" => args.minify = true,
+      "--source-map" => args.source_map = true,
+      "--dev" => args.dev = true,
+      "-h" | "--help" => {
+        println!("{}", USAGE);
+        process::exit(0);
+      }
+      arg if arg.starts_with('-') => return Err(format!("unknown option '{}'", arg)),
+      _ => args.inputs.push(arg.into()),
+    }
+  }
+  if args.inputs.is_empty() {
+    return Err("missing input paths".into());
+  }
+  Ok(args)
+}
+
+impl Args {
+  /// The config file of the options, the `deno.json` of the working directory is used if not specified.
+  fn config_file(&self) -> Option<PathBuf> {
+    match &self.config {
+      Some(config) => Some(self.cwd.join(config)),
+      None => Some(self.cwd.join("deno.json")).filter(|path| path.is_file()),
+    }
+  }
+
+  /// The specifiers of the modules are relative to the directory of the `deno.json` which is also the
+  /// base URL of its import map, or to the working directory otherwise.
+  fn root_dir(&self) -> PathBuf {
+    let root = match self.config_file() {
+      Some(path) if is_deno_json(&path) => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+      _ => self.cwd.clone(),
+    };
+    root.canonicalize().unwrap_or(root)
+  }
+}
+
+fn is_deno_json(path: &Path) -> bool {
+  matches!(path.file_name().and_then(|name| name.to_str()), Some("deno.json"))
+}
+
+/// Load the transform options from the config file, a `deno.json` is mapped to the options by
+/// its import map and `compilerOptions`, other files are parsed as the options directly.
+fn load_options(args: &Args) -> Result<Options, String> {
+  let mut options = match args.config_file() {
+    Some(path) => {
+      let json = fs::read_to_string(&path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+      let invalid = |err: serde_json::Error| format!("invalid config {}: {}", path.display(), err);
+      if is_deno_json(&path) {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        options_from_deno_json(&serde_json::from_str(&json).map_err(invalid)?, dir)?
+      } else {
+        serde_json::from_str(&json).map_err(invalid)?
+      }
+    }
+    None => Options::default(),
+  };
+  if args.minify {
+    options.minify = Some(MinifierOptions { compress: Some(true) });
+  }
+  if args.source_map {
+    options.source_map = Some(true);
+  }
+  if args.dev {
+    options.is_dev = Some(true);
+  }
+  Ok(options)
+}
+
+/// The import map of a `deno.json` is resolved relative to the directory of the file.
+fn options_from_deno_json(json: &Value, dir: &Path) -> Result<Options, String> {
+  let mut options = Options::default();
+  let base_url = dir
+    .canonicalize()
+    .ok()
+    .and_then(|dir| Url::from_directory_path(dir).ok())
+    .ok_or_else(|| format!("could not resolve the directory {}", dir.display()))?;
+  options.import_map_base_url = Some(base_url.to_string());
+  if let Some(import_map) = json.get("importMap").and_then(Value::as_str) {
+    let path = dir.join(import_map);
+    let import_map = fs::read_to_string(&path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+    options.import_map = Some(import_map);
+  } else if json.get("imports").is_some() || json.get("scopes").is_some() {
+    let mut import_map = serde_json::Map::new();
+    for key in ["imports", "scopes"] {
+      if let Some(value) = json.get(key) {
+        import_map.insert(key.into(), value.clone());
+      }
+    }
+    options.import_map = Some(Value::Object(import_map).to_string());
+  }
+  if let Some(compiler_options) = json.get("compilerOptions") {
+    let get = |key: &str| compiler_options.get(key).and_then(Value::as_str).map(str::to_owned);
+    options.jsx = get("jsx").map(|jsx| {
+      match jsx.as_str() {
+        "react-jsx" | "react-jsxdev" => "automatic",
+        "preserve" => "preserve",
+        _ => "classic",
+      }
+      .into()
+    });
+    options.jsx_import_source = get("jsxImportSource");
+    options.jsx_pragma = get("jsxFactory");
+    options.jsx_pragma_frag = get("jsxFragmentFactory");
+  }
+  Ok(options)
+}
+
+fn transform(args: &Args, options: &Options) -> bool {
+  let mut ok = true;
+  let root_dir = args.root_dir();
+  for (root, file) in collect_files(args, &MODULE_EXTS) {
+    let specifier = to_specifier(&file, &root_dir);
+    let output = read_file(&file).and_then(|code| aleph_compiler::transform(&specifier, &code, options));
+    match output {
+      Ok(output) => {
+        print_diagnostics(&output.warnings);
+        ok &= write_output(
+          args,
+          &root,
+          &file,
+          "js",
+          output.code,
+          output.map,
+          "//# sourceMappingURL={}",
+        );
+      }
+      Err(err) => {
+        print_error(&specifier, err);
+        ok = false;
+      }
+    }
+  }
+  ok
+}
+
+fn parse_deps(args: &Args, options: &Options) -> bool {
+  let mut ok = true;
+  let mut deps = BTreeMap::new();
+  let root_dir = args.root_dir();
+  for (_, file) in collect_files(args, &MODULE_EXTS) {
+    let specifier = to_specifier(&file, &root_dir);
+    match read_file(&file).and_then(|code| aleph_compiler::parse_deps(&specifier, &code, options)) {
+      Ok(file_deps) => {
+        deps.insert(specifier, file_deps);
+      }
+      Err(err) => {
+        print_error(&specifier, err);
+        ok = false;
+      }
+    }
+  }
+  println!("{}", serde_json::to_string_pretty(&deps).unwrap());
+  ok
+}
+
+fn compile_css(args: &Args) -> bool {
+  let mut ok = true;
+  let root_dir = args.root_dir();
+  for (root, file) in collect_files(args, &["css"]) {
+    let specifier = to_specifier(&file, &root_dir);
+    let config = CssConfig {
+      minify: Some(args.minify),
+      source_map: Some(args.source_map),
+      css_modules: Some(CssModulesOption::Bool(specifier.ends_with(".module.css"))),
+      ..Default::default()
+    };
+    match read_file(&file).and_then(|code| aleph_compiler::compile_css(&specifier, &code, &config)) {
+      Ok(output) => {
+        print_diagnostics(&output.warnings);
+        ok &= write_output(
+          args,
+          &root,
+          &file,
+          "css",
+          output.code,
+          output.map,
+          "/*# sourceMappingURL={} */",
+        );
+      }
+      Err(err) => {
+        print_error(&specifier, err);
+        ok = false;
+      }
+    }
+  }
+  ok
+}
+
+/// Collect the files with the extensions, the directories are walked recursively and the out dir is
+/// skipped. Returns the files with the input directories they are found in.
+fn collect_files(args: &Args, exts: &[&str]) -> Vec<(PathBuf, PathBuf)> {
+  let out_dir = args
+    .out_dir
+    .as_ref()
+    .and_then(|dir| args.cwd.join(dir).canonicalize().ok());
+  let is_out_dir = |path: &Path| out_dir.is_some() && path.canonicalize().ok() == out_dir;
+  let mut files = vec![];
+  for input in args.inputs.iter().map(|input| args.cwd.join(input)) {
+    if input.is_dir() {
+      walk_dir(&input, exts, &is_out_dir, &mut |file| files.push((input.clone(), file)));
+    } else {
+      let root = input.parent().map(Path::to_path_buf).unwrap_or_default();
+      files.push((root, input));
+    }
+  }
+  files
+}
+
+fn walk_dir(dir: &Path, exts: &[&str], skip_dir: &dyn Fn(&Path) -> bool, push: &mut dyn FnMut(PathBuf)) {
+  let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+    Ok(entries) => entries
+      .filter_map(|entry| entry.ok())
+      .map(|entry| entry.path())
+      .collect(),
+    Err(err) => {
+      eprintln!("warning: could not read {}: {}", dir.display(), err);
+      return;
+    }
+  };
+  entries.sort();
+  for path in entries {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if name.starts_with('.') || name == "node_modules" {
+      continue;
+    }
+    if path.is_dir() {
+      if !skip_dir(&path) {
+        walk_dir(&path, exts, skip_dir, push);
+      }
+    } else if !name.ends_with(".d.ts")
+      && path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| exts.contains(&ext))
+    {
+      push(path);
+    }
+  }
+}
+
+/// Get the specifier of the file relative to the root directory, e.g. `./routes/index.tsx`.
+fn to_specifier(file: &Path, root: &Path) -> String {
+  let path = file
+    .canonicalize()
+    .ok()
+    .and_then(|file| diff_paths(file, root))
+    .unwrap_or_else(|| file.to_path_buf());
+  let path = path.to_slash_lossy().to_string();
+  if path.starts_with("./") || path.starts_with("../") || path.starts_with('/') {
+    path
+  } else {
+    format!("./{}", path)
+  }
+}
+
+fn read_file(file: &Path) -> Result<String, Error> {
+  fs::read_to_string(file).map_err(|err| Error::Other(err.into()))
+}
+
+/// Write the code and the source map to the out dir, or print the code to stdout if there is no out dir.
+fn write_output(
+  args: &Args,
+  root: &Path,
+  file: &Path,
+  ext: &str,
+  code: String,
+  map: Option<String>,
+  source_mapping_url: &str,
+) -> bool {
+  let out_dir = match &args.out_dir {
+    Some(out_dir) => args.cwd.join(out_dir),
+    None => {
+      println!("{}", code);
+      return true;
+    }
+  };
+  let out_file = out_dir
+    .join(file.strip_prefix(root).unwrap_or(file))
+    .with_extension(ext);
+  let write = || -> std::io::Result<()> {
+    if let Some(parent) = out_file.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    match map {
+      Some(map) => {
+        let map_file = out_file.with_extension(format!("{}.map", ext));
+        let map_name = map_file.file_name().unwrap_or_default().to_string_lossy();
+        let code = format!("{}\n{}\n", code.trim_end(), source_mapping_url.replace("{}", &map_name));
+        fs::write(&map_file, map)?;
+        fs::write(&out_file, code)
+      }
+      None => fs::write(&out_file, code),
+    }
+  };
+  match write() {
+    Ok(_) => true,
+    Err(err) => {
+      eprintln!("error: could not write {}: {}", out_file.display(), err);
+      false
+    }
+  }
+}
+
+fn print_error(specifier: &str, err: Error) {
+  match err {
+    Error::Diagnostics(diagnostics) => print_diagnostics(&diagnostics),
+    err => eprintln!("error: {}: {}", specifier, err),
+  }
+}
+
+fn print_diagnostics(diagnostics: &[Diagnostic]) {
+  for diagnostic in diagnostics {
+    let severity = match diagnostic.severity {
+      Severity::Error => "error",
+      Severity::Warning => "warning",
+    };
+    eprintln!("{}: {}", severity, diagnostic);
+    if let Some(code_frame) = &diagnostic.code_frame {
+      eprintln!("{}\n", code_frame);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn args(argv: &[&str]) -> Result<Args, String> {
+    parse_args(argv.iter().map(|arg| arg.to_string()).collect())
+  }
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aleph-compiler-cli-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn parse_command_line() {
+    let parsed = args(&[
+      "transform",
+      "./routes",
+      "-o",
+      "./dist",
+      "--minify",
+      "--source-map",
+      "--dev",
+    ])
+    .unwrap();
+    assert!(parsed.command == Command::Transform);
+    assert_eq!(parsed.inputs, vec![PathBuf::from("./routes")]);
+    assert_eq!(parsed.out_dir, Some(PathBuf::from("./dist")));
+    assert_eq!(parsed.config, None);
+    assert!(parsed.minify && parsed.source_map && parsed.dev);
+
+    let parsed = args(&["parse-deps", "a.ts", "b.ts", "--config", "./config.json"]).unwrap();
+    assert!(parsed.command == Command::ParseDeps);
+    assert_eq!(parsed.inputs, vec![PathBuf::from("a.ts"), PathBuf::from("b.ts")]);
+    assert_eq!(parsed.config, Some(PathBuf::from("./config.json")));
+    assert!(!parsed.minify && !parsed.source_map && !parsed.dev);
+
+    assert_eq!(args(&[]).err().unwrap(), "missing command");
+    assert_eq!(args(&["build", "a.ts"]).err().unwrap(), "unknown command 'build'");
+    assert_eq!(args(&["css"]).err().unwrap(), "missing input paths");
+    assert_eq!(
+      args(&["css", "a.css", "--watch"]).err().unwrap(),
+      "unknown option '--watch'"
+    );
+    assert_eq!(
+      args(&["css", "a.css", "-o"]).err().unwrap(),
+      "missing value of --out-dir"
+    );
+  }
+
+  #[test]
+  fn deno_json_import_map_base_url() {
+    let dir = temp_dir("deno-json");
+    let options = options_from_deno_json(
+      &json!({
+        "imports": { "~/": "./" },
+        "compilerOptions": { "jsx": "react-jsx", "jsxImportSource": "https://esm.sh/react@18" }
+      }),
+      &dir,
+    )
+    .unwrap();
+    let base_url = Url::from_directory_path(dir.canonicalize().unwrap()).unwrap();
+    assert_eq!(options.import_map_base_url, Some(base_url.to_string()));
+    assert!(options.import_map_base_url.unwrap().ends_with('/'));
+    assert_eq!(options.import_map.as_deref(), Some(r#"{"imports":{"~/":"./"}}"#));
+    assert_eq!(options.jsx.as_deref(), Some("automatic"));
+    assert_eq!(options.jsx_import_source.as_deref(), Some("https://esm.sh/react@18"));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn transform_to_out_dir() {
+    let dir = temp_dir("transform");
+    let src = dir.join("src");
+    let out_dir = dir.join("dist");
+    fs::create_dir_all(src.join("routes")).unwrap();
+    fs::write(
+      src.join("app.tsx"),
+      "const name: string = \"aleph\";\nexport default () => <h1>{name}</h1>;\n",
+    )
+    .unwrap();
+    fs::write(src.join("routes/index.ts"), "export const data = { get: () => 1 };\n").unwrap();
+    fs::write(src.join("types.d.ts"), "declare const x: number;\n").unwrap();
+
+    let args = args(&[
+      "transform",
+      src.to_str().unwrap(),
+      "-o",
+      out_dir.to_str().unwrap(),
+      "--source-map",
+    ])
+    .unwrap();
+    assert!(transform(
+      &args,
+      &Options {
+        source_map: Some(true),
+        ..Default::default()
+      }
+    ));
+
+    let code = fs::read_to_string(out_dir.join("app.js")).unwrap();
+    assert!(!code.contains(": string"));
+    assert!(!code.contains("<h1>"));
+    assert!(code.ends_with("//# sourceMappingURL=app.js.map\n"));
+    assert!(out_dir.join("app.js.map").is_file());
+    assert!(out_dir.join("routes/index.js").is_file());
+    assert!(!out_dir.join("types.js").exists());
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn transform_in_subdirectory() {
+    let dir = temp_dir("subdirectory");
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    fs::create_dir_all(dir.join("routes")).unwrap();
+    fs::write(dir.join("deno.json"), r#"{ "imports": { "~/": "./" } }"#).unwrap();
+    fs::write(dir.join("lib/util.ts"), "export const util = 1;\n").unwrap();
+    fs::write(
+      dir.join("routes/index.ts"),
+      "import { util } from \"~/lib/util.ts\";\nexport default util;\n",
+    )
+    .unwrap();
+
+    let mut args = args(&["transform", "index.ts", "-c", "../deno.json", "-o", "../dist"]).unwrap();
+    args.cwd = dir.join("routes");
+    assert_eq!(args.root_dir(), dir.canonicalize().unwrap());
+    assert_eq!(
+      to_specifier(&args.cwd.join("index.ts"), &args.root_dir()),
+      "./routes/index.ts"
+    );
+    assert!(transform(&args, &load_options(&args).unwrap()));
+
+    let code = fs::read_to_string(dir.join("dist/index.js")).unwrap();
+    assert!(code.contains("from \"../lib/util.ts\""));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
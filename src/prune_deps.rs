@@ -0,0 +1,126 @@
+use crate::resolver::Resolver;
+use crate::swc_helpers::{
+  get_entry_url, get_glob_parts, get_import_meta_resolve_asset, get_new_url_asset, get_worker_kind,
+  is_call_expr_by_name, is_service_worker_register, join_glob_parts,
+};
+use std::collections::HashMap;
+use std::{cell::RefCell, rc::Rc};
+use swc_ecmascript::ast::*;
+use swc_ecmascript::visit::{noop_fold_type, noop_visit_type, Fold, Visit, VisitWith};
+
+/// Removes the dependencies whose import/export statements have been removed by the previous
+/// passes, e.g. the imports only used by the stripped data exports are removed by DCE.
+/// The AST is not changed.
+pub fn prune_deps(resolver: Rc<RefCell<Resolver>>) -> impl Fold {
+  PruneDepsFold { resolver }
+}
+
+pub struct PruneDepsFold {
+  resolver: Rc<RefCell<Resolver>>,
+}
+
+impl Fold for PruneDepsFold {
+  noop_fold_type!();
+
+  fn fold_module(&mut self, module: Module) -> Module {
+    let mut collector = ImportUrlCollector::default();
+    module.visit_with(&mut collector);
+    let mut urls = collector.urls;
+    self
+      .resolver
+      .borrow_mut()
+      .deps
+      .retain(|dep| match urls.get_mut(&(dep.import_url.clone(), dep.dynamic)) {
+        Some(count) if *count > 0 => {
+          *count -= 1;
+          true
+        }
+        _ => false,
+      });
+    module
+  }
+}
+
+/// Collects the import urls of the module with the count of references, keyed by the url and
+/// whether it's a dynamic import.
+#[derive(Default)]
+struct ImportUrlCollector {
+  urls: HashMap<(String, bool), usize>,
+}
+
+impl ImportUrlCollector {
+  fn add(&mut self, src: &Str, dynamic: bool) {
+    *self.urls.entry((src.value.to_string(), dynamic)).or_default() += 1;
+  }
+
+  fn add_first_arg(&mut self, args: &[ExprOrSpread]) {
+    if let Some(ExprOrSpread { expr, .. }) = args.first() {
+      if let Expr::Lit(Lit::Str(src)) = expr.as_ref() {
+        self.add(src, true);
+      } else if let Some(parts) = get_glob_parts(expr) {
+        // the import url of a dynamic glob is the pattern, e.g. `./locales/*.ts`
+        *self.urls.entry((join_glob_parts(&parts), true)).or_default() += 1;
+      }
+    }
+  }
+
+  // the worker entry is not visited again, to not count `new URL(...)` as an asset
+  fn add_entry_arg(&mut self, args: &[ExprOrSpread]) {
+    match args.first().and_then(|arg| get_entry_url(&arg.expr)) {
+      Some(src) => {
+        self.add(src, true);
+        args[1..].visit_children_with(self);
+      }
+      None => args.visit_children_with(self),
+    }
+  }
+}
+
+impl Visit for ImportUrlCollector {
+  noop_visit_type!();
+
+  fn visit_import_decl(&mut self, decl: &ImportDecl) {
+    self.add(&decl.src, false);
+  }
+
+  fn visit_named_export(&mut self, export: &NamedExport) {
+    if let Some(src) = &export.src {
+      self.add(src, false);
+    }
+  }
+
+  fn visit_export_all(&mut self, export: &ExportAll) {
+    self.add(&export.src, false);
+  }
+
+  // match: import("./foo.ts")
+  // match: import(`./locales/${lang}.ts`)
+  // match: import.meta.resolve("./logo.png")
+  // match: navigator.serviceWorker.register("./sw.ts")
+  fn visit_call_expr(&mut self, call: &CallExpr) {
+    if is_call_expr_by_name(call, "import") {
+      self.add_first_arg(&call.args);
+    } else if let Some(src) = get_import_meta_resolve_asset(call) {
+      self.add(src, true);
+    } else if is_service_worker_register(call) {
+      self.add_entry_arg(&call.args);
+      return;
+    }
+    call.visit_children_with(self);
+  }
+
+  // match: new Worker("./worker.ts")
+  // match: new SharedWorker(new URL("./worker.ts", import.meta.url))
+  // match: new URL("./logo.png", import.meta.url)
+  fn visit_new_expr(&mut self, new_expr: &NewExpr) {
+    if get_worker_kind(new_expr).is_some() {
+      if let Some(args) = &new_expr.args {
+        self.add_entry_arg(args);
+        return;
+      }
+    } else if let Some(src) = get_new_url_asset(new_expr) {
+      self.add(src, true);
+    }
+    new_expr.visit_children_with(self);
+  }
+}
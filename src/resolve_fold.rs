@@ -1,26 +1,101 @@
-use crate::resolver::Resolver;
-use crate::swc_helpers::{is_call_expr_by_name, new_str};
+use crate::error::emit_warning;
+use crate::resolver::{is_bare_specifier, DependencyKind, Resolver};
+use crate::rsc::{stub_exports, RscConfig};
+use crate::strip_data_export::{strip_data_exports, StripDataExport};
+use crate::swc_helpers::{
+  get_entry_url_mut, get_glob_parts, get_import_meta_resolve_asset, get_new_url_asset, get_worker_kind,
+  is_call_expr_by_name, is_service_worker_register, new_str,
+};
+use std::collections::BTreeMap;
 use std::{cell::RefCell, rc::Rc};
-use swc_common::{Span, DUMMY_SP};
+use swc_common::{Span, Spanned};
 use swc_ecmascript::ast::*;
 use swc_ecmascript::visit::{noop_fold_type, Fold, FoldWith};
 
 pub fn resolve_fold(
   resolver: Rc<RefCell<Resolver>>,
-  strip_data_export: bool,
+  strip_data_export: Option<StripDataExport>,
+  rsc: Option<RscConfig>,
   mark_import_src_location: bool,
+  collect_type_only_deps: bool,
 ) -> impl Fold {
   ResolveFold {
     resolver,
     strip_data_export,
+    rsc,
     mark_import_src_location,
+    collect_type_only_deps,
   }
 }
 
 pub struct ResolveFold {
   resolver: Rc<RefCell<Resolver>>,
-  strip_data_export: bool,
+  strip_data_export: Option<StripDataExport>,
+  rsc: Option<RscConfig>,
   mark_import_src_location: bool,
+  collect_type_only_deps: bool,
+}
+
+impl ResolveFold {
+  /// Resolve the import/export url, and warn if it can't be resolved.
+  fn resolve(&self, src: &Str, kind: DependencyKind) -> String {
+    let mut resolver = self.resolver.borrow_mut();
+    let import_url = resolver.resolve(
+      src.value.as_ref(),
+      kind,
+      mark_span(&src.span, self.mark_import_src_location),
+    );
+    if is_bare_specifier(&import_url) {
+      emit_warning(
+        src.span,
+        "unresolved-import",
+        &format!(
+          "Could not resolve \"{}\": it is neither a relative path nor mapped in the import map",
+          src.value
+        ),
+      );
+    }
+    import_url
+  }
+
+  /// Resolve the worker entry of the first argument, the entry isn't folded again so that
+  /// `new URL("./worker.ts", import.meta.url)` isn't recorded as an asset.
+  fn resolve_entry_args(&mut self, mut args: Vec<ExprOrSpread>, kind: DependencyKind) -> Vec<ExprOrSpread> {
+    let is_document_entry =
+      kind != DependencyKind::Worker && matches!(args.first().map(|arg| arg.expr.as_ref()), Some(Expr::Lit(_)));
+    let entry = match args.first_mut().and_then(|arg| get_entry_url_mut(&mut arg.expr)) {
+      Some(src) => {
+        let mut new_src = self.resolve(src, kind);
+        if is_document_entry {
+          new_src = self.resolver.borrow_mut().fix_document_entry(new_src);
+        }
+        *src = new_str(&new_src);
+        args.remove(0)
+      }
+      None => return args.fold_children_with(self),
+    };
+    let mut args = args.fold_children_with(self);
+    args.insert(0, entry);
+    args
+  }
+
+  /// Resolve the import/export url, and record the names, attributes and type-only flag to the dependency.
+  fn resolve_dep(
+    &self,
+    src: &Str,
+    kind: DependencyKind,
+    names: Vec<String>,
+    type_only: bool,
+    asserts: Option<&ObjectLit>,
+  ) -> String {
+    let import_url = self.resolve(src, kind);
+    if let Some(dep) = self.resolver.borrow_mut().deps.last_mut() {
+      dep.names = names;
+      dep.type_only = type_only;
+      dep.attributes = asserts.map(get_attributes).unwrap_or_default();
+    }
+    import_url
+  }
 }
 
 impl Fold for ResolveFold {
@@ -29,6 +104,15 @@ impl Fold for ResolveFold {
   // fold&resolve import/export url
   fn fold_module_items(&mut self, module_items: Vec<ModuleItem>) -> Vec<ModuleItem> {
     let mut items = Vec::<ModuleItem>::new();
+    let stubs = self.rsc.as_ref().and_then(|rsc| {
+      let resolver = self.resolver.borrow();
+      stub_exports(&module_items, &resolver.specifier, &rsc.runtime, rsc.mode)
+    });
+    let module_items = match (stubs, &self.strip_data_export) {
+      (Some(stubs), _) => stubs,
+      (None, Some(config)) => strip_data_exports(module_items, config),
+      (None, None) => module_items,
+    };
 
     for item in module_items {
       match item {
@@ -36,16 +120,21 @@ impl Fold for ResolveFold {
           let item: ModuleItem = match decl {
             // match: import React, { useState } from "https://esm.sh/react"
             ModuleDecl::Import(import_decl) => {
+              let names = get_import_names(&import_decl.specifiers);
+              let asserts = import_decl.asserts.as_deref();
               if import_decl.type_only {
                 // ingore type import
+                if self.collect_type_only_deps {
+                  self.resolve_dep(&import_decl.src, DependencyKind::Import, names, true, asserts);
+                }
                 ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl))
               } else {
-                let mut resolver = self.resolver.borrow_mut();
-                let resolved_url = resolver.resolve(
-                  import_decl.src.value.as_ref(),
-                  false,
-                  mark_span(&import_decl.src.span, self.mark_import_src_location),
-                );
+                let kind = if import_decl.specifiers.is_empty() {
+                  DependencyKind::SideEffect
+                } else {
+                  DependencyKind::Import
+                };
+                let resolved_url = self.resolve_dep(&import_decl.src, kind, names, false, asserts);
                 ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                   src: Box::new(new_str(&resolved_url)),
                   ..import_decl
@@ -61,8 +150,12 @@ impl Fold for ResolveFold {
               span,
               asserts,
             }) => {
+              let names = get_re_export_names(&specifiers);
               if type_only {
                 // ingore type export
+                if self.collect_type_only_deps {
+                  self.resolve_dep(&src, DependencyKind::ReExport, names, true, asserts.as_deref());
+                }
                 ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
                   span,
                   specifiers,
@@ -71,12 +164,7 @@ impl Fold for ResolveFold {
                   asserts,
                 }))
               } else {
-                let mut resolver = self.resolver.borrow_mut();
-                let resolved_url = resolver.resolve(
-                  src.value.as_ref(),
-                  false,
-                  mark_span(&src.span, self.mark_import_src_location),
-                );
+                let resolved_url = self.resolve_dep(&src, DependencyKind::ReExport, names, false, asserts.as_deref());
                 ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
                   span,
                   specifiers,
@@ -93,170 +181,24 @@ impl Fold for ResolveFold {
               asserts,
               type_only,
             }) => {
-              let mut resolver = self.resolver.borrow_mut();
-              let resolved_url = resolver.resolve(
-                src.value.as_ref(),
-                false,
-                mark_span(&src.span, self.mark_import_src_location),
-              );
-              ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
-                span,
-                src: Box::new(new_str(&resolved_url)),
-                asserts,
-                type_only,
-              }))
-            }
-            // match: export const data = { ... }
-            // match: export const muation = { ... }
-            ModuleDecl::ExportDecl(ExportDecl {
-              decl: Decl::Var(var),
-              span,
-            }) => {
-              let mut data_export_idx = -1;
-              let mut data_export_name = "".to_string();
-              if self.strip_data_export && var.decls.len() > 0 {
-                let mut i = 0;
-                for decl in &var.decls {
-                  if let Pat::Ident(bi) = &decl.name {
-                    if decl.init.is_some() {
-                      match bi.id.sym.as_ref() {
-                        "data" | "mutation" | "GET" | "POST" | "PUT" | "PATCH" | "DELETE" => {
-                          data_export_idx = i;
-                          data_export_name = bi.id.sym.to_string();
-                          break;
-                        }
-                        _ => {}
-                      }
-                    }
-                  }
-                  i += 1;
+              if type_only {
+                // ingore type export
+                if self.collect_type_only_deps {
+                  self.resolve_dep(&src, DependencyKind::ExportAll, vec![], true, asserts.as_deref());
                 }
-              }
-              if data_export_idx != -1 {
-                let mut i = -1;
-                let var = var.as_ref();
-                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
                   span,
-                  decl: Decl::Var(Box::new(VarDecl {
-                    decls: var
-                      .decls
-                      .clone()
-                      .into_iter()
-                      .map(|decl| {
-                        i += 1;
-                        if data_export_idx == i {
-                          let mut init = Some(Box::new(Expr::Lit(Lit::Bool(Bool {
-                            span: DUMMY_SP,
-                            value: true,
-                          }))));
-                          if data_export_name == "data" || data_export_name == "mutation" {
-                            if let Some(expr) = decl.init {
-                              if let Expr::Object(obj) = *expr {
-                                init = Some(Box::new(Expr::Object(ObjectLit {
-                                  span: obj.span,
-                                  props: obj
-                                    .props
-                                    .into_iter()
-                                    .map(|prop| match prop {
-                                      PropOrSpread::Prop(prop) => match *prop {
-                                        Prop::Shorthand(ident) => {
-                                          PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                            key: PropName::Ident(ident),
-                                            value: Box::new(Expr::Lit(Lit::Bool(Bool {
-                                              span: DUMMY_SP,
-                                              value: true,
-                                            }))),
-                                          })))
-                                        }
-                                        Prop::KeyValue(KeyValueProp { key, value, .. }) => {
-                                          // if value is a boolean, we don't need to wrap it
-                                          if let Expr::Lit(Lit::Bool(_)) = *value {
-                                            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp { key, value })))
-                                          } else {
-                                            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                              key,
-                                              value: Box::new(Expr::Lit(Lit::Bool(Bool {
-                                                span: DUMMY_SP,
-                                                value: true,
-                                              }))),
-                                            })))
-                                          }
-                                        }
-                                        Prop::Method(MethodProp { key, .. }) => {
-                                          PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                            key,
-                                            value: Box::new(Expr::Lit(Lit::Bool(Bool {
-                                              span: DUMMY_SP,
-                                              value: true,
-                                            }))),
-                                          })))
-                                        }
-                                        _ => PropOrSpread::Prop(prop),
-                                      },
-                                      _ => prop,
-                                    })
-                                    .collect(),
-                                })))
-                              }
-                            }
-                          }
-                          VarDeclarator {
-                            span: DUMMY_SP,
-                            init,
-                            ..decl
-                          }
-                        } else {
-                          decl
-                        }
-                      })
-                      .collect(),
-                    span: DUMMY_SP,
-                    kind: var.kind,
-                    declare: var.declare,
-                  })),
-                }))
-              } else {
-                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
-                  span,
-                  decl: Decl::Var(var),
-                }))
-              }
-            }
-            // match: export function data/mutation/GET/POST/PUT/PATCH/DELETE { ... }
-            ModuleDecl::ExportDecl(ExportDecl {
-              decl: Decl::Fn(decl),
-              span,
-            }) => {
-              let is_api_method = match decl.ident.sym.as_ref() {
-                "data" | "mutation" | "GET" | "POST" | "PUT" | "PATCH" | "DELETE" => true,
-                _ => false,
-              };
-              if self.strip_data_export && is_api_method {
-                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
-                  span: DUMMY_SP,
-                  decl: Decl::Fn(FnDecl {
-                    ident: decl.ident.clone(),
-                    declare: decl.declare,
-                    function: Box::new(Function {
-                      span: DUMMY_SP,
-                      params: vec![],
-                      decorators: vec![],
-                      // empty body
-                      body: Some(BlockStmt {
-                        span: DUMMY_SP,
-                        stmts: vec![],
-                      }),
-                      is_generator: false,
-                      is_async: false,
-                      type_params: None,
-                      return_type: None,
-                    }),
-                  }),
+                  src,
+                  asserts,
+                  type_only,
                 }))
               } else {
-                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                let resolved_url = self.resolve_dep(&src, DependencyKind::ExportAll, vec![], false, asserts.as_deref());
+                ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
                   span,
-                  decl: Decl::Fn(decl),
+                  src: Box::new(new_str(&resolved_url)),
+                  asserts,
+                  type_only,
                 }))
               }
             }
@@ -273,68 +215,78 @@ impl Fold for ResolveFold {
     items
   }
 
-  // resolve worker import url
+  // resolve worker import url and asset url
   fn fold_new_expr(&mut self, mut new_expr: NewExpr) -> NewExpr {
-    let ok = match new_expr.callee.as_ref() {
-      Expr::Ident(id) => id.sym.as_ref().eq("Worker"),
-      _ => false,
-    };
-    if ok {
-      if let Some(args) = &mut new_expr.args {
-        let src = match args.first() {
-          Some(ExprOrSpread { expr, .. }) => match expr.as_ref() {
-            Expr::Lit(lit) => match lit {
-              Lit::Str(s) => Some(s),
-              _ => None,
-            },
-            _ => None,
-          },
-          _ => None,
-        };
-        if let Some(src) = src {
-          let mut resolver = self.resolver.borrow_mut();
-          let new_src = resolver.resolve(
-            src.value.as_ref(),
-            true,
-            mark_span(&src.span, self.mark_import_src_location),
-          );
+    // match: new URL("./logo.png", import.meta.url)
+    if let Some(src) = get_new_url_asset(&new_expr) {
+      let new_src = self.resolve(src, DependencyKind::Asset);
+      new_expr.args.as_mut().unwrap()[0] = ExprOrSpread {
+        spread: None,
+        expr: Box::new(Expr::Lit(Lit::Str(new_str(&new_src)))),
+      };
+      return new_expr.fold_children_with(self);
+    }
 
-          args[0] = ExprOrSpread {
-            spread: None,
-            expr: Box::new(Expr::Lit(Lit::Str(new_str(&new_src)))),
-          }
-        }
+    // match: new Worker("./worker.ts")
+    // match: new SharedWorker(new URL("./worker.ts", import.meta.url), { type: "module" })
+    if let Some(kind) = get_worker_kind(&new_expr) {
+      if let Some(args) = new_expr.args.take() {
+        new_expr.args = Some(self.resolve_entry_args(args, kind));
+        return new_expr;
       }
-    };
+    }
 
     new_expr.fold_children_with(self)
   }
 
-  // resolve dynamic import url
+  // resolve dynamic import url and asset url
   fn fold_call_expr(&mut self, mut call: CallExpr) -> CallExpr {
+    // match: import.meta.resolve("./logo.png")
+    if let Some(src) = get_import_meta_resolve_asset(&call) {
+      let new_src = self.resolve(src, DependencyKind::Asset);
+      call.args[0] = ExprOrSpread {
+        spread: None,
+        expr: Box::new(Expr::Lit(Lit::Str(new_str(&new_src)))),
+      };
+      return call.fold_children_with(self);
+    }
+
+    // match: navigator.serviceWorker.register("./sw.ts", { scope: "/" })
+    if is_service_worker_register(&call) {
+      let args = std::mem::take(&mut call.args);
+      call.args = self.resolve_entry_args(args, DependencyKind::ServiceWorker);
+      return call;
+    }
+
     if is_call_expr_by_name(&call, "import") {
       let src = match call.args.first() {
         Some(ExprOrSpread { expr, .. }) => match expr.as_ref() {
-          Expr::Lit(lit) => match lit {
-            Lit::Str(s) => Some(s),
-            _ => None,
-          },
+          Expr::Lit(Lit::Str(s)) => Some(s),
+          _ => None,
+        },
+        _ => None,
+      };
+      // match: import("./data.json", { assert: { type: "json" } })
+      let asserts = match call.args.get(1) {
+        Some(ExprOrSpread { expr, .. }) => match expr.as_ref() {
+          Expr::Object(options) => get_dynamic_import_asserts(options),
           _ => None,
         },
         _ => None,
       };
       if let Some(src) = src {
-        let mut resolver = self.resolver.borrow_mut();
-        let new_src = resolver.resolve(
-          src.value.as_ref(),
-          true,
-          mark_span(&src.span, self.mark_import_src_location),
-        );
+        let new_src = self.resolve_dep(src, DependencyKind::DynamicImport, vec![], false, asserts);
 
         call.args[0] = ExprOrSpread {
           spread: None,
           expr: Box::new(Expr::Lit(Lit::Str(new_str(&new_src)))),
         }
+      } else if let Some(ExprOrSpread { expr, .. }) = call.args.first() {
+        // match: import(`./locales/${lang}.ts`)
+        if let Some(parts) = get_glob_parts(expr) {
+          let span = mark_span(&expr.span(), self.mark_import_src_location);
+          self.resolver.borrow_mut().add_dynamic_glob(&parts, span);
+        }
       }
     }
 
@@ -342,9 +294,100 @@ impl Fold for ResolveFold {
   }
 }
 
+// get the names imported by the import specifiers, e.g. `import React, { useState } from "react"` -> `["default", "useState"]`
+pub(crate) fn get_import_names(specifiers: &[ImportSpecifier]) -> Vec<String> {
+  specifiers
+    .iter()
+    .filter_map(|specifier| match specifier {
+      ImportSpecifier::Named(ImportNamedSpecifier {
+        local,
+        imported,
+        is_type_only,
+        ..
+      }) => {
+        if *is_type_only {
+          return None;
+        }
+        Some(match imported {
+          Some(name) => get_module_export_name(name),
+          None => local.sym.to_string(),
+        })
+      }
+      ImportSpecifier::Default(_) => Some("default".into()),
+      ImportSpecifier::Namespace(_) => Some("*".into()),
+    })
+    .collect()
+}
+
+// get the names imported by the re-export specifiers, e.g. `export { default as React, useState } from "react"` -> `["default", "useState"]`
+fn get_re_export_names(specifiers: &[ExportSpecifier]) -> Vec<String> {
+  specifiers
+    .iter()
+    .filter_map(|specifier| match specifier {
+      ExportSpecifier::Named(ExportNamedSpecifier { orig, is_type_only, .. }) => {
+        if *is_type_only {
+          None
+        } else {
+          Some(get_module_export_name(orig))
+        }
+      }
+      ExportSpecifier::Default(_) => Some("default".into()),
+      ExportSpecifier::Namespace(_) => Some("*".into()),
+    })
+    .collect()
+}
+
+fn get_module_export_name(name: &ModuleExportName) -> String {
+  match name {
+    ModuleExportName::Ident(id) => id.sym.to_string(),
+    ModuleExportName::Str(s) => s.value.to_string(),
+  }
+}
+
+// get the import attributes, e.g. `{ type: "json" }`
+fn get_attributes(asserts: &ObjectLit) -> BTreeMap<String, String> {
+  let mut attributes = BTreeMap::new();
+  for prop in &asserts.props {
+    if let PropOrSpread::Prop(prop) = prop {
+      if let Prop::KeyValue(KeyValueProp { key, value }) = prop.as_ref() {
+        let key = match key {
+          PropName::Ident(id) => id.sym.to_string(),
+          PropName::Str(s) => s.value.to_string(),
+          _ => continue,
+        };
+        if let Expr::Lit(Lit::Str(value)) = value.as_ref() {
+          attributes.insert(key, value.value.to_string());
+        }
+      }
+    }
+  }
+  attributes
+}
+
+// get the `assert`/`with` option of the dynamic import, e.g. `{ assert: { type: "json" } }`
+fn get_dynamic_import_asserts(options: &ObjectLit) -> Option<&ObjectLit> {
+  options.props.iter().find_map(|prop| match prop {
+    PropOrSpread::Prop(prop) => match prop.as_ref() {
+      Prop::KeyValue(KeyValueProp { key, value }) => {
+        let is_asserts = match key {
+          PropName::Ident(id) => id.sym.as_ref() == "assert" || id.sym.as_ref() == "with",
+          PropName::Str(s) => s.value.as_ref() == "assert" || s.value.as_ref() == "with",
+          _ => false,
+        };
+        match value.as_ref() {
+          Expr::Object(obj) if is_asserts => Some(obj),
+          _ => None,
+        }
+      }
+      _ => None,
+    },
+    _ => None,
+  })
+}
+
 fn mark_span(span: &Span, ok: bool) -> Option<Span> {
   if ok {
-    Some(span.clone())
+    Some(*span)
   } else {
     None
   }
@@ -1,24 +1,133 @@
+use crate::swc_helpers::join_glob_parts;
+use anyhow::anyhow;
 use import_map::ImportMap;
 use path_slash::PathBufExt;
 use pathdiff::diff_paths;
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 use swc_common::Span;
 use url::Url;
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+/// The kind of the reference to a dependency.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyKind {
+  /// `import React from "react"`
+  Import,
+  /// `export { useState } from "react"`
+  ReExport,
+  /// `export * from "react"`
+  ExportAll,
+  /// `import("react")`
+  DynamicImport,
+  /// `new Worker("./worker.js")`
+  Worker,
+  /// `new SharedWorker("./worker.js")`
+  SharedWorker,
+  /// `navigator.serviceWorker.register("./sw.js")`
+  ServiceWorker,
+  /// `import "./style.css"`
+  SideEffect,
+  /// `new URL("./logo.png", import.meta.url)` or `import.meta.resolve("./logo.png")`
+  Asset,
+  /// `import(`./locales/${lang}.ts`)` or `import("./locales/" + lang + ".ts")`
+  DynamicGlob,
+}
+
+/// The static parts of a `dynamicGlob` dependency.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GlobPattern {
+  /// the static prefix, e.g. `./locales/`
+  pub prefix: String,
+  /// the static suffix, e.g. `.ts`
+  pub suffix: String,
+}
+
+/// A position in the source code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+  /// 1-based line number.
+  pub line: usize,
+  /// 0-based column number in characters.
+  pub column: usize,
+  /// 0-based offset in UTF-16 code units from the start of the source code.
+  pub utf16_offset: usize,
+}
+
+/// The start and end positions of a range in the source code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+  pub start: Position,
+  pub end: Position,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyDescriptor {
   pub specifier: String,
   pub import_url: String,
+  pub kind: DependencyKind,
+  /// the location of the specifier string in the source code, only available with `parseDeps`.
   #[serde(skip_serializing_if = "Option::is_none")]
-  pub loc: Option<Span>,
-  #[serde(skip_serializing_if = "is_false")]
+  pub loc: Option<Location>,
+  #[serde(skip)]
+  pub span: Option<Span>,
+  #[serde(default, skip_serializing_if = "is_false")]
   pub dynamic: bool,
+  /// the names imported from the dependency, `default` for the default import and `*` for the namespace.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub names: Vec<String>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub type_only: bool,
+  /// the import assertions/attributes, e.g. `{ type: "json" }`.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub attributes: BTreeMap<String, String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub integrity: Option<String>,
+  /// the static prefix and suffix of a `dynamicGlob` dependency.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub glob: Option<GlobPattern>,
+}
+
+/// The import map parsed from JSON.
+pub struct ParsedImportMap {
+  pub import_map: Arc<ImportMap>,
+  /// the integrity metadata of the import map, keyed by the resolved URL.
+  pub integrity: Arc<HashMap<String, String>>,
+  /// the non-fatal issues found in the import map.
+  pub diagnostics: Vec<String>,
+}
+
+/// A hook to customize how import/export URLs are resolved, e.g. aliasing, virtual modules
+/// and monorepo packages. With the `parallel` feature the hook is shared by the modules transformed
+/// concurrently, so it must be `Send + Sync`.
+pub trait ResolveHook: HookBounds {
+  /// Called before the import map is applied, return `Some(url)` to replace the specifier.
+  fn resolve(&self, _specifier: &str, _referrer: &str) -> Option<String> {
+    None
+  }
+
+  /// Called with the URL resolved by the import map, return `Some(url)` to replace it.
+  fn after_resolve(&self, _url: &str, _referrer: &str) -> Option<String> {
+    None
+  }
 }
 
+/// The thread-safety bounds of `ResolveHook`, only required by `transform_batch`.
+#[cfg(feature = "parallel")]
+pub trait HookBounds: Send + Sync {}
+#[cfg(feature = "parallel")]
+impl<T: Send + Sync + ?Sized> HookBounds for T {}
+#[cfg(not(feature = "parallel"))]
+pub trait HookBounds {}
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> HookBounds for T {}
+
 /// A Resolver to resolve esm import/export URL.
 pub struct Resolver {
   /// aleph pkg uri
@@ -34,19 +143,27 @@ pub struct Resolver {
   /// the global version
   pub global_version: Option<String>,
   /// the graph versions
-  pub graph_versions: HashMap<String, String>,
+  pub graph_versions: Arc<HashMap<String, String>>,
   /// should resolve remote deps
   pub resolve_remote_deps: bool,
+  /// the hook provided by the host to customize resolving
+  pub hook: Option<Arc<dyn ResolveHook>>,
+  /// the integrity metadata of the import map
+  pub integrity: Arc<HashMap<String, String>>,
+  /// the CDN origin to rewrite `npm:`, `jsr:` and `node:` specifiers, e.g. `https://esm.sh`
+  pub cdn_origin: Option<String>,
+  /// the candidate files of `import.meta.glob` provided by the host, e.g. `./routes/index.tsx`
+  pub glob_files: Option<Vec<String>>,
   // import maps
-  import_map: ImportMap,
+  import_map: Arc<ImportMap>,
 }
 
 impl Resolver {
   pub fn new(
     specifier: &str,
     aleph_pkg_uri: &str,
-    import_map: ImportMap,
-    graph_versions: HashMap<String, String>,
+    import_map: impl Into<Arc<ImportMap>>,
+    graph_versions: impl Into<Arc<HashMap<String, String>>>,
     global_version: Option<String>,
     resolve_remote_deps: bool,
     is_dev: bool,
@@ -56,11 +173,15 @@ impl Resolver {
       specifier: specifier.into(),
       specifier_is_remote: is_http_url(specifier),
       deps: Vec::new(),
-      import_map,
-      graph_versions,
+      import_map: import_map.into(),
+      graph_versions: graph_versions.into(),
       global_version,
       is_dev,
       resolve_remote_deps,
+      hook: None,
+      integrity: Arc::default(),
+      cdn_origin: None,
+      glob_files: None,
     }
   }
 
@@ -69,7 +190,10 @@ impl Resolver {
   //  - `https://deno.land/std/path/mod.ts` -> `/-/deno.land/std/path/mod.ts`
   //  - `http://localhost:8080/mod.ts` -> `/-/http_localhost_8080/mod.ts`
   pub fn to_local_path(&self, url: &str) -> String {
-    let url = Url::from_str(url).unwrap();
+    let url = match Url::from_str(url) {
+      Ok(parsed) if parsed.host_str().is_some() => parsed,
+      _ => return url.into(),
+    };
     let pathname = Path::new(url.path());
     let mut local_path = "/-/".to_owned();
     let scheme = url.scheme();
@@ -78,14 +202,13 @@ impl Resolver {
     }
     local_path.push_str(url.host_str().unwrap());
     if let Some(port) = url.port() {
-      if scheme == "http" && port == 80 {
-      } else if scheme == "https" && port == 443 {
-      } else {
+      let is_default_port = (scheme == "http" && port == 80) || (scheme == "https" && port == 443);
+      if !is_default_port {
         local_path.push('_');
         local_path.push_str(port.to_string().as_str());
       }
     }
-    local_path.push_str(&pathname.to_owned().to_slash().unwrap().to_string());
+    local_path.push_str(pathname.to_owned().to_slash().unwrap().as_ref());
     if let Some(query) = url.query() {
       local_path.push('?');
       local_path.push_str(query);
@@ -93,24 +216,76 @@ impl Resolver {
     local_path
   }
 
-  /// Resolve import/export URLs.
-  pub fn resolve(&mut self, url: &str, dynamic: bool, loc: Option<Span>) -> String {
-    let referrer = if self.specifier_is_remote {
+  /// rewrite `npm:`, `jsr:` and `node:` specifiers to the CDN origin.
+  //  - `npm:react@18` -> `https://esm.sh/react@18`
+  //  - `jsr:@std/path` -> `https://esm.sh/jsr/@std/path`
+  //  - `node:fs` -> `https://esm.sh/node/fs.js`
+  pub fn to_cdn_url(&self, url: &str) -> Option<String> {
+    let origin = self.cdn_origin.as_ref()?.trim_end_matches('/');
+    if let Some(pkg) = url.strip_prefix("npm:") {
+      Some(format!("{}/{}", origin, pkg.trim_start_matches('/')))
+    } else if let Some(pkg) = url.strip_prefix("jsr:") {
+      Some(format!("{}/jsr/{}", origin, pkg.trim_start_matches('/')))
+    } else {
+      url
+        .strip_prefix("node:")
+        .map(|name| format!("{}/node/{}.js", origin, name))
+    }
+  }
+
+  /// check if the url is served by esm.sh or the CDN origin.
+  fn is_cdn_url(&self, url: &str) -> bool {
+    is_esm_sh_url(url)
+      || match &self.cdn_origin {
+        Some(origin) => url.starts_with(&(origin.trim_end_matches('/').to_owned() + "/")),
+        None => false,
+      }
+  }
+
+  /// the referrer URL of the module for the import map, local specifiers are relative to the
+  /// base URL of the import map, e.g. `./pages/index.tsx` -> `file:///pages/index.tsx`.
+  fn referrer(&self) -> Url {
+    if self.specifier_is_remote {
       Url::from_str(self.specifier.as_str()).unwrap()
     } else {
-      Url::from_str(&("file://".to_owned() + self.specifier.trim_start_matches('.'))).unwrap()
+      let path = ".".to_owned() + self.specifier.trim_start_matches('.');
+      self.import_map.base_url().join(&path).unwrap()
+    }
+  }
+
+  /// get the local path of the url if it's under the base URL of the import map,
+  /// e.g. `file:///components/button.tsx` -> `/components/button.tsx`.
+  fn to_base_path(&self, url: &str) -> Option<String> {
+    let base_url = self.import_map.base_url().join("./").ok()?;
+    url.strip_prefix(base_url.as_str()).map(|path| "/".to_owned() + path)
+  }
+
+  /// Resolve import/export URLs.
+  pub fn resolve(&mut self, url: &str, kind: DependencyKind, span: Option<Span>) -> String {
+    let referrer = self.referrer();
+    let hook = self.hook.clone();
+    let url = match &hook {
+      Some(hook) => hook.resolve(url, &self.specifier).unwrap_or_else(|| url.into()),
+      None => url.into(),
     };
-    let resolved_url = if let Ok(ret) = self.import_map.resolve(url, &referrer) {
+    let mut resolved_url = if let Ok(ret) = self.import_map.resolve(&url, &referrer) {
       ret.to_string()
     } else {
-      url.into()
+      url
     };
-    let mut import_url = if resolved_url.starts_with("file://") {
-      let path = resolved_url.strip_prefix("file://").unwrap();
+    if let Some(hook) = &hook {
+      if let Some(url) = hook.after_resolve(&resolved_url, &self.specifier) {
+        resolved_url = url;
+      }
+    }
+    let integrity = self.integrity.get(&resolved_url).cloned();
+    let base_path = self.to_base_path(&resolved_url);
+    let cdn_url = self.to_cdn_url(&resolved_url);
+    let mut import_url = if let Some(path) = &base_path {
       if !self.specifier_is_remote {
         let mut buf = PathBuf::from(self.specifier.trim_start_matches('.'));
         buf.pop();
-        let mut path = diff_paths(&path, buf).unwrap().to_slash().unwrap().to_string();
+        let mut path = diff_paths(path, buf).unwrap().to_slash().unwrap().to_string();
         if !path.starts_with("./") && !path.starts_with("../") {
           path = "./".to_owned() + &path
         }
@@ -119,29 +294,33 @@ impl Resolver {
         ".".to_owned() + path
       }
     } else {
-      resolved_url.clone()
+      cdn_url.clone().unwrap_or_else(|| resolved_url.clone())
     };
-    let mut fixed_url: String = if resolved_url.starts_with("file://") {
-      ".".to_owned() + resolved_url.strip_prefix("file://").unwrap()
+    let mut fixed_url: String = if let Some(path) = base_path {
+      ".".to_owned() + &path
     } else {
-      resolved_url.into()
+      resolved_url
     };
-    let is_remote = is_http_url(&fixed_url);
+    let is_remote = is_http_url(&import_url);
 
-    if self.is_dev && is_esm_sh_url(&fixed_url) && !fixed_url.ends_with(".development.js") {
-      if fixed_url.contains("?") {
-        fixed_url = fixed_url + "&dev"
+    if self.is_dev && self.is_cdn_url(&import_url) && !import_url.ends_with(".development.js") {
+      if import_url.contains("?") {
+        import_url += "&dev"
       } else {
-        fixed_url = fixed_url + "?dev"
+        import_url += "?dev"
+      }
+      // keep the original `npm:`/`jsr:`/`node:` specifier
+      if cdn_url.is_none() {
+        fixed_url = import_url.clone();
       }
-      import_url = fixed_url.clone();
     }
 
-    if is_css_url(&import_url) {
+    // assets are fetched as they are, not imported as modules
+    if kind != DependencyKind::Asset && is_css_url(&import_url) {
       if import_url.contains("?") {
-        import_url = import_url + "&module"
+        import_url += "&module"
       } else {
-        import_url = import_url + "?module"
+        import_url += "?module"
       }
     }
 
@@ -150,7 +329,7 @@ impl Resolver {
       if self.resolve_remote_deps {
         import_url = self.to_local_path(&import_url);
       }
-    } else {
+    } else if !is_package_specifier(&import_url) {
       // apply graph version if exists
       let v = if self.graph_versions.contains_key(&fixed_url) {
         self.graph_versions.get(&fixed_url)
@@ -170,20 +349,143 @@ impl Resolver {
     self.deps.push(DependencyDescriptor {
       specifier: fixed_url.clone(),
       import_url: import_url.clone(),
-      loc,
-      dynamic,
+      kind,
+      loc: None,
+      span,
+      dynamic: matches!(
+        kind,
+        DependencyKind::DynamicImport
+          | DependencyKind::Worker
+          | DependencyKind::SharedWorker
+          | DependencyKind::ServiceWorker
+          | DependencyKind::Asset
+      ),
+      names: vec![],
+      type_only: false,
+      attributes: BTreeMap::new(),
+      integrity,
+      glob: None,
     });
 
     import_url
   }
+
+  /// Make the local import URL of the last dependency root-absolute, e.g. `../../sw.ts?v=1` -> `/sw.ts?v=1`.
+  /// It's for the entries of `new SharedWorker("...")` and `navigator.serviceWorker.register("...")` given
+  /// as plain strings, which are resolved against the document URL rather than the module.
+  pub fn fix_document_entry(&mut self, import_url: String) -> String {
+    if !import_url.starts_with("./") && !import_url.starts_with("../") {
+      return import_url;
+    }
+    let (path, query) = match import_url.split_once('?') {
+      Some((path, query)) => (path, Some(query)),
+      None => (import_url.as_str(), None),
+    };
+    // the local path of a remote module is relative to the root already, e.g. `./components/button.tsx`
+    let dir = match self.specifier.rsplit_once('/') {
+      Some((dir, _)) if !self.specifier_is_remote => dir,
+      _ => "",
+    };
+    let mut segments: Vec<&str> = vec![];
+    for segment in dir.split('/').chain(path.split('/')) {
+      match segment {
+        "." | "" => {}
+        ".." => {
+          segments.pop();
+        }
+        segment => segments.push(segment),
+      }
+    }
+    let mut root_url = "/".to_owned() + &segments.join("/");
+    if let Some(query) = query {
+      root_url = root_url + "?" + query;
+    }
+    if let Some(dep) = self.deps.last_mut() {
+      dep.import_url = root_url.clone();
+    }
+    root_url
+  }
+
+  /// Record a dynamic import with a computed specifier, the static parts are joined with `*` as
+  /// the glob pattern, e.g. `["./locales/", ".ts"]` -> `./locales/*.ts`. The pattern is resolved
+  /// with the import map but not versioned.
+  pub fn add_dynamic_glob(&mut self, parts: &[String], span: Option<Span>) {
+    let pattern = join_glob_parts(parts);
+    let specifier = match self.import_map.resolve(&pattern, &self.referrer()) {
+      Ok(url) => match self.to_base_path(url.as_str()) {
+        Some(path) => ".".to_owned() + &path,
+        None => url.to_string(),
+      },
+      Err(_) => pattern.clone(),
+    };
+    self.deps.push(DependencyDescriptor {
+      specifier,
+      import_url: pattern,
+      kind: DependencyKind::DynamicGlob,
+      loc: None,
+      span,
+      dynamic: true,
+      names: vec![],
+      type_only: false,
+      attributes: BTreeMap::new(),
+      integrity: None,
+      glob: Some(GlobPattern {
+        prefix: parts[0].clone(),
+        suffix: parts[parts.len() - 1].clone(),
+      }),
+    });
+  }
+}
+
+/// Parse the import map JSON with the base URL, the `integrity` metadata is parsed separately
+/// since the import map parser doesn't support it.
+pub fn parse_import_map(json: &str, base_url: &str) -> Result<ParsedImportMap, anyhow::Error> {
+  let base_url =
+    Url::from_str(base_url).map_err(|err| anyhow!("Invalid import map base URL \"{}\": {}", base_url, err))?;
+  let mut value: Value = serde_json::from_str(json).map_err(|err| anyhow!("Invalid import map: {}", err))?;
+  let mut integrity = HashMap::new();
+  let mut diagnostics = vec![];
+  if let Some(metadata) = value.as_object_mut().and_then(|map| map.remove("integrity")) {
+    match metadata {
+      Value::Object(metadata) => {
+        for (key, hash) in metadata {
+          match (base_url.join(&key), hash.as_str()) {
+            (Ok(url), Some(hash)) => {
+              integrity.insert(url.to_string(), hash.to_owned());
+            }
+            _ => diagnostics.push(format!("Invalid integrity metadata for \"{}\".", key)),
+          }
+        }
+      }
+      _ => diagnostics.push("The \"integrity\" top-level key must be a JSON object.".into()),
+    }
+  }
+  let ret = import_map::parse_from_value(&base_url, value).map_err(|err| anyhow!("Invalid import map: {}", err))?;
+  diagnostics.extend(ret.diagnostics.iter().map(|d| d.to_string()));
+
+  Ok(ParsedImportMap {
+    import_map: Arc::new(ret.import_map),
+    integrity: Arc::new(integrity),
+    diagnostics,
+  })
 }
 
 pub fn is_http_url(url: &str) -> bool {
-  return url.starts_with("https://") || url.starts_with("http://");
+  url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// Check if the url is a bare specifier like `react`, which is neither a relative path nor an URL.
+pub fn is_bare_specifier(url: &str) -> bool {
+  !(url.starts_with("./") || url.starts_with("../") || url.starts_with('/') || Url::from_str(url).is_ok())
+}
+
+/// Check if the url is a package specifier of the `npm:`, `jsr:` or `node:` scheme.
+pub fn is_package_specifier(url: &str) -> bool {
+  url.starts_with("npm:") || url.starts_with("jsr:") || url.starts_with("node:")
 }
 
 pub fn is_esm_sh_url(url: &str) -> bool {
-  return url.starts_with("https://esm.sh/") || url.starts_with("http://esm.sh/");
+  url.starts_with("https://esm.sh/") || url.starts_with("http://esm.sh/")
 }
 
 pub fn is_css_url(url: &str) -> bool {
@@ -195,9 +497,9 @@ pub fn is_css_url(url: &str) -> bool {
       }
     }
   }
-  return url.ends_with(".css") || url.contains(".css?");
+  url.ends_with(".css") || url.contains(".css?")
 }
 
 fn is_false(value: &bool) -> bool {
-  return !*value;
+  !*value
 }
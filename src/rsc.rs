@@ -0,0 +1,169 @@
+use crate::error::emit_warning;
+use crate::exports::get_exports;
+use crate::swc_helpers::{import_name, new_str, pat_id};
+use serde::{Deserialize, Serialize};
+use swc_common::DUMMY_SP;
+use swc_ecmascript::ast::*;
+use swc_ecmascript::utils::quote_ident;
+
+/// The module directives of React Server Components.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Directive {
+  #[serde(rename = "use client")]
+  UseClient,
+  #[serde(rename = "use server")]
+  UseServer,
+}
+
+/// The build target of React Server Components.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RscMode {
+  /// The server build, the exports of `"use client"` modules are replaced with client references.
+  Server,
+  /// The client build, the exports of `"use server"` modules are replaced with server references.
+  Client,
+}
+
+/// The React Server Components options of a transform.
+#[derive(Clone, Debug)]
+pub struct RscConfig {
+  pub mode: RscMode,
+  /// The module exporting `registerClientReference` and `createServerReference`.
+  pub runtime: String,
+}
+
+/// Get the `"use client"` and `"use server"` directives in the directive prologue of the module.
+pub fn get_directives(items: &[ModuleItem]) -> Vec<Directive> {
+  let mut directives = vec![];
+  for item in items {
+    match item {
+      ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => match expr.as_ref() {
+        Expr::Lit(Lit::Str(Str { value, .. })) => match value.as_ref() {
+          "use client" => directives.push(Directive::UseClient),
+          "use server" => directives.push(Directive::UseServer),
+          _ => {}
+        },
+        _ => break,
+      },
+      _ => break,
+    }
+  }
+  directives
+}
+
+/// Replace the module items with the reference stubs of the exports, if it's a `"use client"` module
+/// in the server build or a `"use server"` module in the client build, otherwise returns `None`.
+///
+/// server build: `export const Button = registerClientReference("./button.tsx", "Button")`
+/// client build: `export const createTodo = createServerReference("./actions.ts", "createTodo")`
+pub fn stub_exports(
+  items: &[ModuleItem],
+  specifier: &str,
+  runtime_url: &str,
+  mode: RscMode,
+) -> Option<Vec<ModuleItem>> {
+  let directives = get_directives(items);
+  let create_reference = match mode {
+    RscMode::Server if directives.contains(&Directive::UseClient) => "registerClientReference",
+    RscMode::Client if directives.contains(&Directive::UseServer) => "createServerReference",
+    _ => return None,
+  };
+
+  // import { registerClientReference } from "$rscRuntime"
+  let mut stubs = vec![ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+    span: DUMMY_SP,
+    specifiers: vec![import_name(create_reference)],
+    src: Box::new(new_str(runtime_url)),
+    type_only: false,
+    asserts: None,
+  }))];
+
+  for item in items {
+    if let ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
+      span,
+      src,
+      type_only: false,
+      ..
+    })) = item
+    {
+      emit_warning(
+        *span,
+        "unsupported-export-all",
+        &format!(
+          "Could not create the references of `export * from \"{}\"`, re-export the names explicitly instead",
+          src.value
+        ),
+      );
+    }
+  }
+
+  for (i, export) in get_exports(items).into_iter().enumerate() {
+    if export.name == "*" {
+      continue;
+    }
+    let reference = Expr::Call(CallExpr {
+      span: DUMMY_SP,
+      callee: Callee::Expr(Box::new(Expr::Ident(quote_ident!(create_reference)))),
+      args: vec![new_str_arg(specifier), new_str_arg(&export.name)],
+      type_args: None,
+    });
+    if export.name == "default" {
+      // export default registerClientReference("./button.tsx", "default")
+      stubs.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+        ExportDefaultExpr {
+          span: DUMMY_SP,
+          expr: Box::new(reference),
+        },
+      )));
+    } else if Ident::verify_symbol(&export.name).is_ok() {
+      // export const Button = registerClientReference("./button.tsx", "Button")
+      stubs.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+        span: DUMMY_SP,
+        decl: Decl::Var(Box::new(new_const_decl(&export.name, reference))),
+      })));
+    } else {
+      // const _ref0 = registerClientReference("./button.tsx", "my-button")
+      // export { _ref0 as "my-button" }
+      let local = format!("_ref{}", i);
+      stubs.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(new_const_decl(
+        &local, reference,
+      ))))));
+      stubs.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+        span: DUMMY_SP,
+        specifiers: vec![ExportSpecifier::Named(ExportNamedSpecifier {
+          span: DUMMY_SP,
+          orig: ModuleExportName::Ident(quote_ident!(local)),
+          exported: Some(ModuleExportName::Str(new_str(&export.name))),
+          is_type_only: false,
+        })],
+        src: None,
+        type_only: false,
+        asserts: None,
+      })));
+    }
+  }
+
+  Some(stubs)
+}
+
+fn new_str_arg(s: &str) -> ExprOrSpread {
+  ExprOrSpread {
+    spread: None,
+    expr: Box::new(Expr::Lit(Lit::Str(new_str(s)))),
+  }
+}
+
+fn new_const_decl(name: &str, init: Expr) -> VarDecl {
+  VarDecl {
+    span: DUMMY_SP,
+    kind: VarDeclKind::Const,
+    declare: false,
+    decls: vec![VarDeclarator {
+      span: DUMMY_SP,
+      name: pat_id(name),
+      init: Some(Box::new(init)),
+      definite: false,
+    }],
+  }
+}
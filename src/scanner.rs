@@ -0,0 +1,656 @@
+use crate::error::{syntax_error_code, DiagnosticBuffer, ErrorBuffer};
+use crate::resolver::{DependencyDescriptor, DependencyKind, Resolver};
+use crate::swc::{get_location, get_syntax};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::{cell::RefCell, rc::Rc};
+use swc_common::errors::DiagnosticId;
+use swc_common::{FileName, SourceMap, Span};
+use swc_ecmascript::ast::AssignOp;
+use swc_ecmascript::ast::EsVersion;
+use swc_ecmascript::parser::lexer::Lexer;
+use swc_ecmascript::parser::token::{BinOpToken, Keyword, Token, TokenAndSpan, Word};
+use swc_ecmascript::parser::StringInput;
+
+/// Scan the imports/exports of the module with the lexer only, without building the AST.
+/// It's much faster than `SWC::parse_deps` for crawling large module graphs, but only the
+/// static `import`/`export` statements, dynamic imports with string literals, workers, service workers,
+/// `new URL("...", import.meta.url)` and `import.meta.resolve("...")` are recognized,
+/// the `import.meta.glob(...)` calls are not expanded.
+pub fn scan_deps(
+  specifier: &str,
+  source: &str,
+  lang: Option<String>,
+  resolver: Rc<RefCell<Resolver>>,
+  type_only_deps: bool,
+) -> Result<Vec<DependencyDescriptor>, anyhow::Error> {
+  let source_map = SourceMap::default();
+  let source_file = source_map.new_source_file(FileName::Real(Path::new(specifier).to_path_buf()), source.into());
+  let mut lexer = Lexer::new(
+    get_syntax(specifier, lang),
+    EsVersion::Es2022,
+    StringInput::from(&*source_file),
+    None,
+  );
+  let mut tokens: Vec<TokenAndSpan> = Vec::new();
+  while let Some(mut token) = lexer.next() {
+    // the lexer can't tell a regexp from a division without the parser, re-read it as a regexp
+    // if an expression is expected, e.g. `const re = /import("foo")/`.
+    if matches!(
+      token.token,
+      Token::BinOp(BinOpToken::Div) | Token::AssignOp(AssignOp::DivAssign)
+    ) && is_expr_allowed(tokens.last().map(|t| &t.token))
+    {
+      lexer.set_next_regexp(Some(token.span.lo));
+      token = match lexer.next() {
+        Some(token) => token,
+        None => break,
+      };
+      lexer.set_next_regexp(None);
+    }
+    if let Token::Error(err) = token.token {
+      let error_buffer = ErrorBuffer::new(specifier);
+      let handler = error_buffer.handler();
+      let code = syntax_error_code(err.kind());
+      let mut diagnostic = err.into_diagnostic(&handler);
+      diagnostic.code(DiagnosticId::Error(code.into()));
+      diagnostic.emit();
+      return Err(DiagnosticBuffer::from_error_buffer(error_buffer, &source_map).into());
+    }
+    tokens.push(token);
+  }
+
+  let mut scanner = Scanner {
+    tokens,
+    pos: 0,
+    deps: vec![],
+  };
+  scanner.scan();
+
+  let mut resolver = resolver.borrow_mut();
+  for dep in scanner.deps {
+    if dep.type_only && !type_only_deps {
+      continue;
+    }
+    if dep.kind == DependencyKind::DynamicGlob {
+      resolver.add_dynamic_glob(&dep.glob, Some(dep.span));
+    } else {
+      let import_url = resolver.resolve(&dep.specifier, dep.kind, Some(dep.span));
+      if dep.document_entry {
+        resolver.fix_document_entry(import_url);
+      }
+    }
+    if let Some(last) = resolver.deps.last_mut() {
+      last.loc = Some(get_location(&source_map, dep.span));
+      last.names = dep.names;
+      last.type_only = dep.type_only;
+      last.attributes = dep.attributes;
+    }
+  }
+  Ok(resolver.deps.clone())
+}
+
+/// Check if an expression is allowed after the token, a `}` is treated as the end of a block.
+fn is_expr_allowed(prev: Option<&Token>) -> bool {
+  match prev {
+    None | Some(Token::RBrace) => true,
+    Some(token) => token.before_expr(),
+  }
+}
+
+fn is_global_object(sym: &swc_atoms::JsWord) -> bool {
+  matches!(sym.as_ref(), "window" | "self" | "globalThis")
+}
+
+/// A dependency found by the scanner, before resolving.
+struct ScannedDep {
+  specifier: String,
+  kind: DependencyKind,
+  span: Span,
+  names: Vec<String>,
+  type_only: bool,
+  attributes: BTreeMap<String, String>,
+  /// the static parts of a `DynamicGlob` dependency
+  glob: Vec<String>,
+  /// a shared worker or service worker entry given as a string, it's relative to the document
+  document_entry: bool,
+}
+
+struct Scanner {
+  tokens: Vec<TokenAndSpan>,
+  pos: usize,
+  deps: Vec<ScannedDep>,
+}
+
+impl Scanner {
+  fn scan(&mut self) {
+    while self.pos < self.tokens.len() {
+      // skip member access like `foo.import(...)` or `foo.export`
+      let after_dot = self.pos > 0 && matches!(self.tokens[self.pos - 1].token, Token::Dot);
+      let start = self.pos;
+      match &self.tokens[self.pos].token {
+        Token::Word(Word::Keyword(Keyword::Import)) if !after_dot => {
+          self.pos += 1;
+          self.scan_import();
+        }
+        Token::Word(Word::Keyword(Keyword::Export)) if !after_dot => {
+          self.pos += 1;
+          self.scan_export();
+        }
+        Token::Word(Word::Keyword(Keyword::New)) => {
+          self.pos += 1;
+          self.scan_new();
+        }
+        Token::Word(Word::Ident(sym))
+          if sym.as_ref() == "navigator" && (!after_dot || self.after_global(start - 1)) =>
+        {
+          self.pos += 1;
+          self.scan_service_worker();
+        }
+        _ => {}
+      }
+      if self.pos == start {
+        self.pos += 1;
+      }
+    }
+  }
+
+  // match: import("./foo.ts")
+  // match: import "./style.css"
+  // match: import React, { useState } from "react"
+  // match: import type { FC } from "react"
+  fn scan_import(&mut self) {
+    match self.peek() {
+      Some(Token::LParen) => {
+        self.pos += 1;
+        if let Some((glob, span)) = self.scan_dynamic_glob() {
+          self.deps.push(ScannedDep {
+            specifier: String::new(),
+            kind: DependencyKind::DynamicGlob,
+            span,
+            names: vec![],
+            type_only: false,
+            attributes: BTreeMap::new(),
+            glob,
+            document_entry: false,
+          });
+          return;
+        }
+        if let Some((specifier, span)) = self.eat_str() {
+          let attributes = match self.peek() {
+            Some(Token::RParen) => BTreeMap::new(),
+            Some(Token::Comma) => {
+              self.pos += 1;
+              self.scan_dynamic_import_attributes()
+            }
+            _ => return,
+          };
+          self.push(
+            specifier,
+            DependencyKind::DynamicImport,
+            span,
+            vec![],
+            false,
+            attributes,
+          );
+        }
+      }
+      // match: import.meta.resolve("./logo.png")
+      Some(Token::Dot) => {
+        if self.eat_member("meta") && self.eat_member("resolve") && self.eat(|t| matches!(t, Token::LParen)) {
+          if let Some((specifier, span)) = self.eat_str() {
+            if self.eat(|t| matches!(t, Token::RParen)) {
+              self.push(specifier, DependencyKind::Asset, span, vec![], false, BTreeMap::new());
+            }
+          }
+        }
+      }
+      Some(Token::Str { .. }) => {
+        let (specifier, span) = self.eat_str().unwrap();
+        let attributes = self.scan_attributes();
+        self.push(specifier, DependencyKind::SideEffect, span, vec![], false, attributes);
+      }
+      _ => {
+        let type_only = self.eat_type_modifier();
+        let names = self.scan_import_clause();
+        if self.eat_ident("from") {
+          if let Some((specifier, span)) = self.eat_str() {
+            let attributes = self.scan_attributes();
+            self.push(specifier, DependencyKind::Import, span, names, type_only, attributes);
+          }
+        }
+      }
+    }
+  }
+
+  // match: export * from "./foo.ts"
+  // match: export * as foo from "./foo.ts"
+  // match: export { default as foo, bar } from "./foo.ts"
+  // match: export type { Foo } from "./foo.ts"
+  fn scan_export(&mut self) {
+    let type_only = self.eat_type_modifier();
+    match self.peek() {
+      Some(Token::BinOp(BinOpToken::Mul)) => {
+        self.pos += 1;
+        let (kind, names) = if self.eat_ident("as") {
+          self.eat_name();
+          (DependencyKind::ReExport, vec!["*".to_owned()])
+        } else {
+          (DependencyKind::ExportAll, vec![])
+        };
+        if self.eat_ident("from") {
+          if let Some((specifier, span)) = self.eat_str() {
+            let attributes = self.scan_attributes();
+            self.push(specifier, kind, span, names, type_only, attributes);
+          }
+        }
+      }
+      Some(Token::LBrace) => {
+        let names = self.scan_named_specifiers();
+        if self.eat_ident("from") {
+          if let Some((specifier, span)) = self.eat_str() {
+            let attributes = self.scan_attributes();
+            self.push(specifier, DependencyKind::ReExport, span, names, type_only, attributes);
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  // match: new Worker("./worker.ts")
+  // match: new window.SharedWorker(new URL("./worker.ts", import.meta.url), { type: "module" })
+  // match: new URL("./logo.png", import.meta.url)
+  fn scan_new(&mut self) {
+    let is_global = matches!(self.peek(), Some(Token::Word(Word::Ident(sym))) if is_global_object(sym))
+      && matches!(self.peek_nth(1), Some(Token::Dot));
+    if is_global {
+      self.pos += 2;
+    }
+    if self.eat_ident("Worker") {
+      self.scan_entry(DependencyKind::Worker);
+    } else if self.eat_ident("SharedWorker") {
+      self.scan_entry(DependencyKind::SharedWorker);
+    } else if !is_global && self.eat_ident("URL") {
+      self.scan_new_url(DependencyKind::Asset);
+    }
+  }
+
+  // match: navigator.serviceWorker.register("./sw.ts", { scope: "/" })
+  fn scan_service_worker(&mut self) {
+    if self.eat_member("serviceWorker") && self.eat_member("register") {
+      self.scan_entry(DependencyKind::ServiceWorker);
+    }
+  }
+
+  // match: ("./worker.ts")
+  // match: (new URL("./worker.ts", import.meta.url))
+  fn scan_entry(&mut self, kind: DependencyKind) {
+    if !self.eat(|t| matches!(t, Token::LParen)) {
+      return;
+    }
+    if let Some((specifier, span)) = self.eat_str() {
+      self.push(specifier, kind, span, vec![], false, BTreeMap::new());
+      if let Some(dep) = self.deps.last_mut() {
+        dep.document_entry = kind != DependencyKind::Worker;
+      }
+    } else if self.eat(|t| matches!(t, Token::Word(Word::Keyword(Keyword::New)))) && self.eat_ident("URL") {
+      self.scan_new_url(kind);
+    }
+  }
+
+  // match: ("./logo.png", import.meta.url)
+  fn scan_new_url(&mut self, kind: DependencyKind) {
+    if !self.eat(|t| matches!(t, Token::LParen)) {
+      return;
+    }
+    if let Some((specifier, span)) = self.eat_str() {
+      if self.eat(|t| matches!(t, Token::Comma))
+        && self.eat(|t| matches!(t, Token::Word(Word::Keyword(Keyword::Import))))
+        && self.eat_member("meta")
+        && self.eat_member("url")
+        && self.eat(|t| matches!(t, Token::RParen))
+      {
+        self.push(specifier, kind, span, vec![], false, BTreeMap::new());
+      }
+    }
+  }
+
+  // check if the token before the dot at `pos` is `window`, `self` or `globalThis`
+  fn after_global(&self, pos: usize) -> bool {
+    pos > 0
+      && matches!(&self.tokens[pos - 1].token, Token::Word(Word::Ident(sym)) if is_global_object(sym))
+      && (pos < 2 || !matches!(self.tokens[pos - 2].token, Token::Dot))
+  }
+
+  // match: React, { useState as useS, type FC }
+  // match: * as React
+  fn scan_import_clause(&mut self) -> Vec<String> {
+    let mut names = vec![];
+    loop {
+      match self.peek() {
+        Some(Token::LBrace) => names.extend(self.scan_named_specifiers()),
+        Some(Token::BinOp(BinOpToken::Mul)) => {
+          self.pos += 1;
+          if self.eat_ident("as") {
+            self.eat_name();
+          }
+          names.push("*".to_owned());
+        }
+        Some(Token::Word(Word::Ident(sym))) if sym.as_ref() != "from" => {
+          self.pos += 1;
+          names.push("default".to_owned());
+        }
+        _ => break,
+      }
+      if !self.eat(|t| matches!(t, Token::Comma)) {
+        break;
+      }
+    }
+    names
+  }
+
+  // match: { default as foo, bar, type Baz, "string name" as qux }
+  // returns the names on the left side of `as`, skipping type-only specifiers.
+  fn scan_named_specifiers(&mut self) -> Vec<String> {
+    let mut names = vec![];
+    if !self.eat(|t| matches!(t, Token::LBrace)) {
+      return names;
+    }
+    loop {
+      if self.eat(|t| matches!(t, Token::RBrace)) {
+        break;
+      }
+      let type_only = self.eat_type_modifier();
+      match self.eat_name() {
+        Some(name) => {
+          if self.eat_ident("as") {
+            self.eat_name();
+          }
+          if !type_only {
+            names.push(name);
+          }
+        }
+        None => break,
+      }
+      if !self.eat(|t| matches!(t, Token::Comma)) {
+        self.eat(|t| matches!(t, Token::RBrace));
+        break;
+      }
+    }
+    names
+  }
+
+  // match: assert { type: "json" }
+  // match: with { type: "json" }
+  fn scan_attributes(&mut self) -> BTreeMap<String, String> {
+    let is_attributes_keyword = match self.peek() {
+      Some(Token::Word(Word::Ident(sym))) => sym.as_ref() == "assert",
+      Some(Token::Word(Word::Keyword(Keyword::With))) => true,
+      _ => false,
+    };
+    if is_attributes_keyword && matches!(self.peek_nth(1), Some(Token::LBrace)) {
+      self.pos += 1;
+      self.scan_object_entries()
+    } else {
+      BTreeMap::new()
+    }
+  }
+
+  // match: (`./locales/${lang}.ts`)
+  // match: ("./locales/" + lang + ".ts")
+  // returns the static parts like `get_glob_parts` does with the AST, the position is restored if
+  // the argument is not a glob.
+  fn scan_dynamic_glob(&mut self) -> Option<(Vec<String>, Span)> {
+    let start = self.pos;
+    let ret = self.scan_glob_parts();
+    if ret.is_none() {
+      self.pos = start;
+    }
+    ret
+  }
+
+  fn scan_glob_parts(&mut self) -> Option<(Vec<String>, Span)> {
+    let lo = self.tokens.get(self.pos)?.span.lo;
+    let mut parts = vec![String::new()];
+    if matches!(self.peek(), Some(Token::BackQuote)) {
+      self.pos += 1;
+      loop {
+        match self.peek()? {
+          Token::Template { cooked, raw } => {
+            let value = cooked.as_ref().unwrap_or(raw).to_string();
+            parts.last_mut().unwrap().push_str(&value);
+            self.pos += 1;
+          }
+          Token::DollarLBrace => {
+            self.skip_balanced()?;
+            parts.push(String::new());
+          }
+          Token::BackQuote => {
+            self.pos += 1;
+            break;
+          }
+          _ => return None,
+        }
+      }
+    } else {
+      // split the operands by `+`, only the string literals are static
+      let mut operand_start = self.pos;
+      let mut dynamic = false;
+      loop {
+        let end = matches!(self.peek()?, Token::RParen | Token::Comma);
+        if end || matches!(self.peek(), Some(Token::BinOp(BinOpToken::Add))) {
+          match &self.tokens[operand_start..self.pos] {
+            [TokenAndSpan {
+              token: Token::Str { value, .. },
+              ..
+            }] => {
+              dynamic = false;
+              parts.last_mut().unwrap().push_str(value);
+            }
+            [] => return None,
+            _ if !dynamic => {
+              dynamic = true;
+              parts.push(String::new());
+            }
+            _ => {}
+          }
+          if end {
+            break;
+          }
+          self.pos += 1;
+          operand_start = self.pos;
+          continue;
+        }
+        match self.peek()? {
+          Token::LParen | Token::LBracket | Token::LBrace | Token::DollarLBrace | Token::BackQuote => {
+            self.skip_balanced()?
+          }
+          // the operators binding tighter than `+`
+          Token::BinOp(BinOpToken::Mul | BinOpToken::Div | BinOpToken::Mod | BinOpToken::Exp)
+          | Token::Dot
+          | Token::Word(Word::Ident(_) | Word::Null | Word::True | Word::False)
+          | Token::Word(Word::Keyword(Keyword::This | Keyword::TypeOf | Keyword::Void))
+          | Token::Str { .. }
+          | Token::Num { .. }
+          | Token::BigInt { .. } => self.pos += 1,
+          // unary operators
+          Token::BinOp(BinOpToken::Sub) | Token::Bang | Token::Tilde if self.pos == operand_start => self.pos += 1,
+          _ => return None,
+        }
+      }
+    }
+    let hi = self.tokens[self.pos - 1].span.hi;
+    if !matches!(self.peek(), Some(Token::RParen | Token::Comma)) || parts.len() < 2 || parts[0].is_empty() {
+      return None;
+    }
+    Some((parts, Span::new(lo, hi, Default::default())))
+  }
+
+  // skip the tokens in the brackets or the template literal, including the closing one.
+  fn skip_balanced(&mut self) -> Option<()> {
+    let mut stack = vec![];
+    loop {
+      let token = self.peek()?;
+      let in_template = matches!(stack.last(), Some(Token::BackQuote));
+      match token {
+        Token::BackQuote if in_template => {
+          stack.pop();
+        }
+        Token::LParen | Token::LBracket | Token::LBrace | Token::DollarLBrace | Token::BackQuote => {
+          stack.push(token.clone())
+        }
+        Token::RParen | Token::RBracket | Token::RBrace => {
+          stack.pop()?;
+        }
+        _ => {}
+      }
+      self.pos += 1;
+      if stack.is_empty() {
+        return Some(());
+      }
+    }
+  }
+
+  // match: { assert: { type: "json" } }
+  fn scan_dynamic_import_attributes(&mut self) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::new();
+    if !self.eat(|t| matches!(t, Token::LBrace)) {
+      return attributes;
+    }
+    if let Some(key) = self.eat_name() {
+      if (key == "assert" || key == "with") && self.eat(|t| matches!(t, Token::Colon)) {
+        attributes = self.scan_object_entries();
+      }
+    }
+    // skip the rest of the options
+    let mut depth = 1;
+    while depth > 0 && self.pos < self.tokens.len() {
+      match self.tokens[self.pos].token {
+        Token::LBrace => depth += 1,
+        Token::RBrace => depth -= 1,
+        _ => {}
+      }
+      self.pos += 1;
+    }
+    attributes
+  }
+
+  // match: { type: "json" }
+  fn scan_object_entries(&mut self) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+    if !self.eat(|t| matches!(t, Token::LBrace)) {
+      return entries;
+    }
+    while let Some(key) = self.eat_name() {
+      if !self.eat(|t| matches!(t, Token::Colon)) {
+        break;
+      }
+      match self.eat_str() {
+        Some((value, _)) => entries.insert(key, value),
+        None => break,
+      };
+      if !self.eat(|t| matches!(t, Token::Comma)) {
+        break;
+      }
+    }
+    self.eat(|t| matches!(t, Token::RBrace));
+    entries
+  }
+
+  fn push(
+    &mut self,
+    specifier: String,
+    kind: DependencyKind,
+    span: Span,
+    names: Vec<String>,
+    type_only: bool,
+    attributes: BTreeMap<String, String>,
+  ) {
+    self.deps.push(ScannedDep {
+      specifier,
+      kind,
+      span,
+      names,
+      type_only,
+      attributes,
+      glob: vec![],
+      document_entry: false,
+    });
+  }
+
+  fn peek(&self) -> Option<&Token> {
+    self.peek_nth(0)
+  }
+
+  fn peek_nth(&self, n: usize) -> Option<&Token> {
+    self.tokens.get(self.pos + n).map(|t| &t.token)
+  }
+
+  fn eat(&mut self, pred: impl Fn(&Token) -> bool) -> bool {
+    if self.peek().map(pred).unwrap_or_default() {
+      self.pos += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn eat_ident(&mut self, name: &str) -> bool {
+    self.eat(|t| matches!(t, Token::Word(Word::Ident(sym)) if sym.as_ref() == name))
+  }
+
+  // eat a member access like `.meta`
+  fn eat_member(&mut self, name: &str) -> bool {
+    if matches!(self.peek(), Some(Token::Dot))
+      && matches!(self.peek_nth(1), Some(Token::Word(Word::Ident(sym))) if sym.as_ref() == name)
+    {
+      self.pos += 2;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn eat_str(&mut self) -> Option<(String, Span)> {
+    match self.tokens.get(self.pos) {
+      Some(TokenAndSpan {
+        token: Token::Str { value, .. },
+        span,
+        ..
+      }) => {
+        let ret = (value.to_string(), *span);
+        self.pos += 1;
+        Some(ret)
+      }
+      _ => None,
+    }
+  }
+
+  // eat an identifier, a keyword or a string literal as a name.
+  fn eat_name(&mut self) -> Option<String> {
+    let name = match self.peek()? {
+      Token::Word(word) => swc_atoms::JsWord::from(word.clone()).to_string(),
+      Token::Str { value, .. } => value.to_string(),
+      _ => return None,
+    };
+    self.pos += 1;
+    Some(name)
+  }
+
+  // eat the `type` modifier of TS, `import type from "./foo.ts"` imports the default export named `type`.
+  fn eat_type_modifier(&mut self) -> bool {
+    let is_type = matches!(self.peek(), Some(Token::Word(Word::Ident(sym))) if sym.as_ref() == "type");
+    let is_modifier = match self.peek_nth(1) {
+      Some(Token::Word(Word::Ident(sym))) => sym.as_ref() != "from" && sym.as_ref() != "as",
+      Some(Token::Comma) | Some(Token::RBrace) | None => false,
+      Some(_) => true,
+    };
+    if is_type && is_modifier {
+      self.pos += 1;
+      true
+    } else {
+      false
+    }
+  }
+}
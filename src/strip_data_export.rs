@@ -0,0 +1,565 @@
+use crate::error::emit_warning;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use swc_common::DUMMY_SP;
+use swc_ecmascript::ast::*;
+use swc_ecmascript::utils::{find_pat_ids, private_ident};
+
+/// The default server-only exports of aleph routes.
+const DEFAULT_DATA_EXPORT_NAMES: [&str; 7] = ["data", "mutation", "GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// The `stripDataExport` option, accepts:
+/// - `true` to strip the default exports: `data`, `mutation`, `GET`, `POST`, `PUT`, `PATCH` and `DELETE`
+/// - a list of export names, e.g. `["loader", "action"]`
+/// - an object with the export names and/or a pattern, e.g. `{ pattern: "^(loader|action)$" }`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StripDataExportOption {
+  Enabled(bool),
+  Names(Vec<String>),
+  #[serde(rename_all = "camelCase")]
+  Config {
+    names: Option<Vec<String>>,
+    pattern: Option<String>,
+  },
+}
+
+impl StripDataExportOption {
+  pub fn into_config(self) -> Result<Option<StripDataExport>, anyhow::Error> {
+    Ok(match self {
+      StripDataExportOption::Enabled(true) => Some(StripDataExport::default()),
+      StripDataExportOption::Enabled(false) => None,
+      StripDataExportOption::Names(names) => Some(StripDataExport { names, pattern: None }),
+      StripDataExportOption::Config { names, pattern } => {
+        let pattern = match pattern {
+          Some(pattern) => {
+            Some(Regex::new(&pattern).map_err(|err| anyhow::anyhow!("Invalid stripDataExport pattern: {}", err))?)
+          }
+          None => None,
+        };
+        let names = match names {
+          Some(names) => names,
+          None if pattern.is_some() => vec![],
+          None => StripDataExport::default().names,
+        };
+        Some(StripDataExport { names, pattern })
+      }
+    })
+  }
+}
+
+/// The server-only exports to strip, matched by the export name.
+#[derive(Clone, Debug)]
+pub struct StripDataExport {
+  pub names: Vec<String>,
+  pub pattern: Option<Regex>,
+}
+
+impl Default for StripDataExport {
+  fn default() -> Self {
+    StripDataExport {
+      names: DEFAULT_DATA_EXPORT_NAMES.iter().map(|name| name.to_string()).collect(),
+      pattern: None,
+    }
+  }
+}
+
+impl StripDataExport {
+  pub fn is_match(&self, name: &str) -> bool {
+    self.names.iter().any(|n| n == name) || self.pattern.as_ref().map(|p| p.is_match(name)).unwrap_or_default()
+  }
+}
+
+/// Strip the server-only exports of the module, the exports are kept as stubs so the client can tell
+/// which exports exist:
+/// - `export const data = { get: () => {} }` -> `export const data = { get: true }`
+/// - `export const GET = () => {}` or `export const GET = { ... }` -> `export const GET = true`
+/// - `export function GET() { ... }` -> `export function GET() {}`
+/// - `export const { GET, POST } = handlers` -> `export const GET = true, POST = true`
+/// - `export default { GET() { ... } }` -> `export default { GET: true }`
+/// - `export { data }` -> stubs the local declaration or the import of `data`
+/// - `export { data } from "./data.ts"` -> `export const data = true`
+pub fn strip_data_exports(items: Vec<ModuleItem>, config: &StripDataExport) -> Vec<ModuleItem> {
+  let locals = get_exported_locals(&items, config);
+  let mut stripped = Vec::with_capacity(items.len());
+
+  for item in items {
+    match item {
+      ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, span })) => {
+        let decl = strip_decl(decl, &|name| config.is_match(name));
+        stripped.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+          decl,
+          span,
+        })));
+      }
+      // match: export { data } from "./data.ts"
+      ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+        span,
+        specifiers,
+        src: Some(src),
+        type_only: false,
+        asserts,
+      })) => {
+        let mut stubs = vec![];
+        let specifiers: Vec<ExportSpecifier> = specifiers
+          .into_iter()
+          .filter(|specifier| {
+            let exported = match specifier {
+              ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) => exported.as_ref().unwrap_or(orig),
+              ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => name,
+              ExportSpecifier::Default(_) => return true,
+            };
+            match exported {
+              ModuleExportName::Ident(id) if config.is_match(id.sym.as_ref()) => {
+                stubs.push(VarDeclarator {
+                  span: DUMMY_SP,
+                  name: Pat::Ident(BindingIdent {
+                    id: id.clone(),
+                    type_ann: None,
+                  }),
+                  init: Some(Box::new(new_true())),
+                  definite: false,
+                });
+                false
+              }
+              _ => true,
+            }
+          })
+          .collect();
+        if !specifiers.is_empty() {
+          stripped.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+            span,
+            specifiers,
+            src: Some(src),
+            type_only: false,
+            asserts,
+          })));
+        }
+        if !stubs.is_empty() {
+          stripped.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            span: DUMMY_SP,
+            decl: Decl::Var(Box::new(new_const_decl(stubs))),
+          })));
+        }
+      }
+      // match: export default { data: { ... }, GET() { ... } }
+      ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { span, expr })) => {
+        let expr = match *expr {
+          Expr::Object(obj) => Box::new(Expr::Object(strip_object_props(obj, &|name| config.is_match(name)))),
+          expr => Box::new(expr),
+        };
+        stripped.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+          ExportDefaultExpr { span, expr },
+        )));
+      }
+      // match: import { data } from "./data.ts"; export { data }
+      ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl)) if !locals.is_empty() && !import_decl.type_only => {
+        let mut stubs = vec![];
+        let specifiers: Vec<ImportSpecifier> = import_decl
+          .specifiers
+          .into_iter()
+          .filter(|specifier| {
+            let local = match specifier {
+              ImportSpecifier::Named(ImportNamedSpecifier { local, .. }) => local,
+              ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => local,
+              ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => local,
+            };
+            if locals.contains(local.sym.as_ref()) {
+              stubs.push(VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent {
+                  id: local.clone(),
+                  type_ann: None,
+                }),
+                init: Some(Box::new(new_true())),
+                definite: false,
+              });
+              false
+            } else {
+              true
+            }
+          })
+          .collect();
+        // keep the side-effect import if there are no specifiers at all
+        if !specifiers.is_empty() || stubs.is_empty() {
+          stripped.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+            specifiers,
+            ..import_decl
+          })));
+        }
+        if !stubs.is_empty() {
+          stripped.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(new_const_decl(stubs))))));
+        }
+      }
+      // match: const data = { ... }; export { data }
+      ModuleItem::Stmt(Stmt::Decl(decl)) if !locals.is_empty() => {
+        let decl = strip_decl(decl, &|name| locals.contains(name));
+        stripped.push(ModuleItem::Stmt(Stmt::Decl(decl)));
+      }
+      _ => stripped.push(item),
+    }
+  }
+
+  stripped
+}
+
+// get the local names of `export { local as exported }` whose exported name matches, and warn the
+// ones that are neither declared nor imported in the module.
+fn get_exported_locals(items: &[ModuleItem], config: &StripDataExport) -> HashSet<String> {
+  let mut locals = HashSet::new();
+  let mut bindings = HashSet::new();
+  for item in items {
+    match item {
+      ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+        specifiers,
+        src: None,
+        type_only: false,
+        ..
+      })) => {
+        for specifier in specifiers {
+          if let ExportSpecifier::Named(ExportNamedSpecifier {
+            orig: ModuleExportName::Ident(orig),
+            exported,
+            is_type_only: false,
+            ..
+          }) = specifier
+          {
+            let exported = match exported {
+              Some(ModuleExportName::Ident(id)) => id.sym.as_ref(),
+              Some(ModuleExportName::Str(s)) => s.value.as_ref(),
+              None => orig.sym.as_ref(),
+            };
+            if config.is_match(exported) {
+              locals.insert((orig.sym.to_string(), orig.span));
+            }
+          }
+        }
+      }
+      ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { specifiers, .. })) => {
+        for specifier in specifiers {
+          let local = match specifier {
+            ImportSpecifier::Named(ImportNamedSpecifier { local, .. }) => local,
+            ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => local,
+            ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => local,
+          };
+          bindings.insert(local.sym.to_string());
+        }
+      }
+      ModuleItem::Stmt(Stmt::Decl(decl)) => match decl {
+        Decl::Fn(FnDecl { ident, .. }) => {
+          bindings.insert(ident.sym.to_string());
+        }
+        Decl::Var(var) => {
+          for decl in &var.decls {
+            let ids: Vec<Id> = find_pat_ids(&decl.name);
+            bindings.extend(ids.into_iter().map(|(sym, _)| sym.to_string()));
+          }
+        }
+        _ => {}
+      },
+      _ => {}
+    }
+  }
+
+  let mut names = HashSet::new();
+  for (name, span) in locals {
+    if bindings.contains(&name) {
+      names.insert(name);
+    } else {
+      emit_warning(
+        span,
+        "unstripped-data-export",
+        &format!(
+          "The `{}` export is not stripped, it's neither declared nor imported in the module",
+          name
+        ),
+      );
+    }
+  }
+  names
+}
+
+// strip the matched function/variable declarations.
+fn strip_decl(decl: Decl, is_match: &dyn Fn(&str) -> bool) -> Decl {
+  match decl {
+    // match: export function GET() { ... }
+    Decl::Fn(decl) if is_match(decl.ident.sym.as_ref()) => Decl::Fn(FnDecl {
+      ident: decl.ident,
+      declare: decl.declare,
+      function: Box::new(Function {
+        span: DUMMY_SP,
+        params: vec![],
+        decorators: vec![],
+        // empty body
+        body: Some(BlockStmt {
+          span: DUMMY_SP,
+          stmts: vec![],
+        }),
+        is_generator: false,
+        is_async: false,
+        type_params: None,
+        return_type: None,
+      }),
+    }),
+    // match: export const data = { ... }, GET = () => { ... }
+    // match: export const { GET, POST } = handlers
+    Decl::Var(var) => {
+      let mut decls = Vec::with_capacity(var.decls.len());
+      for decl in var.decls {
+        match decl.name {
+          Pat::Ident(ref bi) => {
+            if is_match(bi.id.sym.as_ref()) && decl.init.is_some() {
+              decls.push(VarDeclarator {
+                span: DUMMY_SP,
+                init: decl.init.map(|init| Box::new(stub_expr(bi.id.sym.as_ref(), *init))),
+                ..decl
+              });
+            } else {
+              decls.push(decl);
+            }
+          }
+          name => {
+            let mut stripped = vec![];
+            let name = remove_pat_names(name, is_match, &mut stripped);
+            if let Some(name) = name {
+              decls.push(VarDeclarator { name, ..decl });
+            }
+            for id in stripped {
+              decls.push(VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent { id, type_ann: None }),
+                init: Some(Box::new(new_true())),
+                definite: false,
+              });
+            }
+          }
+        }
+      }
+      Decl::Var(Box::new(VarDecl { decls, ..*var }))
+    }
+    _ => decl,
+  }
+}
+
+// `key: _name`, the identifier is private so it never clashes with the other bindings.
+fn unused_key_value(key: PropName, removed: Option<&Ident>) -> ObjectPatProp {
+  let name = removed.map(|id| id.sym.as_ref()).unwrap_or_default();
+  ObjectPatProp::KeyValue(KeyValuePatProp {
+    key,
+    value: Box::new(Pat::Ident(BindingIdent {
+      id: private_ident!(format!("_{}", name)),
+      type_ann: None,
+    })),
+  })
+}
+
+// remove the matched names from the destructuring pattern, returns `None` if nothing is left.
+fn remove_pat_names(pat: Pat, is_match: &dyn Fn(&str) -> bool, removed: &mut Vec<Ident>) -> Option<Pat> {
+  match pat {
+    Pat::Ident(bi) => {
+      if is_match(bi.id.sym.as_ref()) {
+        removed.push(bi.id);
+        None
+      } else {
+        Some(Pat::Ident(bi))
+      }
+    }
+    Pat::Object(obj) => {
+      // with a `...rest` element the removed keys are kept and bound to unused identifiers, otherwise
+      // the rest object would collect them, e.g. `{ GET, ...rest }` -> `{ GET: _GET, ...rest }`
+      let has_rest = obj.props.iter().any(|prop| matches!(prop, ObjectPatProp::Rest(_)));
+      let props: Vec<ObjectPatProp> = obj
+        .props
+        .into_iter()
+        .filter_map(|prop| match prop {
+          ObjectPatProp::KeyValue(KeyValuePatProp { key, value }) => {
+            match remove_pat_names(*value, is_match, removed) {
+              Some(value) => Some(ObjectPatProp::KeyValue(KeyValuePatProp {
+                key,
+                value: Box::new(value),
+              })),
+              None if has_rest => Some(unused_key_value(key, removed.last())),
+              None => None,
+            }
+          }
+          ObjectPatProp::Assign(AssignPatProp { key, value, span }) => {
+            if is_match(key.sym.as_ref()) {
+              removed.push(key.clone());
+              if has_rest {
+                Some(unused_key_value(PropName::Ident(key), removed.last()))
+              } else {
+                None
+              }
+            } else {
+              Some(ObjectPatProp::Assign(AssignPatProp { key, value, span }))
+            }
+          }
+          ObjectPatProp::Rest(RestPat {
+            span,
+            dot3_token,
+            arg,
+            type_ann,
+          }) => remove_pat_names(*arg, is_match, removed).map(|arg| {
+            ObjectPatProp::Rest(RestPat {
+              span,
+              dot3_token,
+              arg: Box::new(arg),
+              type_ann,
+            })
+          }),
+        })
+        .collect();
+      if props.is_empty() {
+        None
+      } else {
+        Some(Pat::Object(ObjectPat { props, ..obj }))
+      }
+    }
+    Pat::Array(arr) => {
+      let elems: Vec<Option<Pat>> = arr
+        .elems
+        .into_iter()
+        .map(|elem| elem.and_then(|elem| remove_pat_names(elem, is_match, removed)))
+        .collect();
+      if elems.iter().all(|elem| elem.is_none()) {
+        None
+      } else {
+        Some(Pat::Array(ArrayPat { elems, ..arr }))
+      }
+    }
+    Pat::Assign(AssignPat {
+      span,
+      left,
+      right,
+      type_ann,
+    }) => remove_pat_names(*left, is_match, removed).map(|left| {
+      Pat::Assign(AssignPat {
+        span,
+        left: Box::new(left),
+        right,
+        type_ann,
+      })
+    }),
+    Pat::Rest(RestPat {
+      span,
+      dot3_token,
+      arg,
+      type_ann,
+    }) => remove_pat_names(*arg, is_match, removed).map(|arg| {
+      Pat::Rest(RestPat {
+        span,
+        dot3_token,
+        arg: Box::new(arg),
+        type_ann,
+      })
+    }),
+    pat => Some(pat),
+  }
+}
+
+// strip the matched props of the object, e.g. `{ data: { ... }, GET() { ... } }` -> `{ data: { ... }, GET: true }`
+fn strip_object_props(obj: ObjectLit, is_match: &dyn Fn(&str) -> bool) -> ObjectLit {
+  ObjectLit {
+    span: obj.span,
+    props: obj
+      .props
+      .into_iter()
+      .map(|prop| match prop {
+        PropOrSpread::Prop(prop) => {
+          let prop = match *prop {
+            Prop::Shorthand(ident) if is_match(ident.sym.as_ref()) => Prop::KeyValue(KeyValueProp {
+              key: PropName::Ident(ident),
+              value: Box::new(new_true()),
+            }),
+            Prop::KeyValue(KeyValueProp { key, value }) if is_prop_name_match(&key, is_match) => {
+              let value = stub_expr(get_prop_name(&key).unwrap_or_default(), *value);
+              Prop::KeyValue(KeyValueProp {
+                key,
+                value: Box::new(value),
+              })
+            }
+            Prop::Method(MethodProp { key, .. }) if is_prop_name_match(&key, is_match) => {
+              Prop::KeyValue(KeyValueProp {
+                key,
+                value: Box::new(new_true()),
+              })
+            }
+            prop => prop,
+          };
+          PropOrSpread::Prop(Box::new(prop))
+        }
+        _ => prop,
+      })
+      .collect(),
+  }
+}
+
+// stub the value of a server-only export, the `data` and `mutation` objects keep their keys with `true`
+// values, e.g. `{ defer: true, fetch: () => {} }` -> `{ defer: true, fetch: true }`, other values are
+// replaced with `true`.
+fn stub_expr(name: &str, expr: Expr) -> Expr {
+  match expr {
+    Expr::Object(obj) if name == "data" || name == "mutation" => Expr::Object(ObjectLit {
+      span: obj.span,
+      props: obj
+        .props
+        .into_iter()
+        .map(|prop| match prop {
+          PropOrSpread::Prop(prop) => {
+            let prop = match *prop {
+              Prop::Shorthand(ident) => Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(ident),
+                value: Box::new(new_true()),
+              }),
+              // if value is a boolean, we don't need to wrap it
+              Prop::KeyValue(KeyValueProp { key, value }) => match *value {
+                Expr::Lit(Lit::Bool(_)) => Prop::KeyValue(KeyValueProp { key, value }),
+                _ => Prop::KeyValue(KeyValueProp {
+                  key,
+                  value: Box::new(new_true()),
+                }),
+              },
+              Prop::Method(MethodProp { key, .. }) => Prop::KeyValue(KeyValueProp {
+                key,
+                value: Box::new(new_true()),
+              }),
+              prop => prop,
+            };
+            PropOrSpread::Prop(Box::new(prop))
+          }
+          _ => prop,
+        })
+        .collect(),
+    }),
+    _ => new_true(),
+  }
+}
+
+fn is_prop_name_match(key: &PropName, is_match: &dyn Fn(&str) -> bool) -> bool {
+  get_prop_name(key).map(is_match).unwrap_or_default()
+}
+
+fn get_prop_name(key: &PropName) -> Option<&str> {
+  match key {
+    PropName::Ident(id) => Some(id.sym.as_ref()),
+    PropName::Str(s) => Some(s.value.as_ref()),
+    _ => None,
+  }
+}
+
+fn new_const_decl(decls: Vec<VarDeclarator>) -> VarDecl {
+  VarDecl {
+    span: DUMMY_SP,
+    kind: VarDeclKind::Const,
+    declare: false,
+    decls,
+  }
+}
+
+fn new_true() -> Expr {
+  Expr::Lit(Lit::Bool(Bool {
+    span: DUMMY_SP,
+    value: true,
+  }))
+}
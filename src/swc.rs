@@ -1,20 +1,28 @@
-use crate::error::{DiagnosticBuffer, ErrorBuffer};
+use crate::define::define;
+use crate::error::{get_utf16_offset, syntax_error_code, Diagnostic, DiagnosticBuffer, ErrorBuffer, Severity};
 use crate::hmr::hmr;
+use crate::import_glob::import_glob;
+use crate::jsx_runtime::jsx_runtime;
 use crate::minifier::{MinifierOptions, MinifierPass};
+use crate::prune_deps::prune_deps;
 use crate::resolve_fold::resolve_fold;
-use crate::resolver::{DependencyDescriptor, Resolver};
+use crate::resolver::{DependencyDescriptor, Location, Position, Resolver};
+use crate::rsc::{get_directives, Directive, RscConfig};
+use crate::strip_data_export::StripDataExport;
 
+use std::collections::HashMap;
 use std::{cell::RefCell, path::Path, rc::Rc};
 use swc_common::comments::SingleThreadedComments;
-use swc_common::errors::{Handler, HandlerFlags};
-use swc_common::{chain, FileName, Globals, Mark, SourceMap};
-use swc_ecma_transforms::optimization::simplify::dce;
+use swc_common::errors::{DiagnosticId, HANDLER};
+use swc_common::{chain, BytePos, FileName, Globals, Mark, SourceMap, Span, Spanned};
+use swc_ecma_transforms::optimization::simplify::{self, dce};
 use swc_ecma_transforms::pass::Optional;
 use swc_ecma_transforms::proposals::decorators;
 use swc_ecma_transforms::typescript::strip;
 use swc_ecma_transforms::{compat, fixer, helpers, hygiene, react, Assumptions};
 use swc_ecmascript::ast::{EsVersion, Module, Program};
 use swc_ecmascript::codegen::text_writer::JsWriter;
+use swc_ecmascript::parser::error::Error as ParserError;
 use swc_ecmascript::parser::lexer::Lexer;
 use swc_ecmascript::parser::{EsConfig, StringInput, Syntax, TsConfig};
 use swc_ecmascript::visit::{as_folder, Fold, FoldWith};
@@ -28,7 +36,9 @@ pub struct EmitOptions {
   pub jsx_pragma_frag: Option<String>,
   pub jsx_import_source: Option<String>,
   pub react_refresh: bool,
-  pub strip_data_export: bool,
+  pub strip_data_export: Option<StripDataExport>,
+  pub rsc: Option<RscConfig>,
+  pub define: HashMap<String, serde_json::Value>,
   pub minify: Option<MinifierOptions>,
   pub source_map: bool,
 }
@@ -42,73 +52,148 @@ impl Default for EmitOptions {
       jsx_pragma_frag: None,
       jsx_import_source: None,
       react_refresh: false,
-      strip_data_export: false,
+      strip_data_export: None,
+      rsc: None,
+      define: HashMap::new(),
       minify: None,
       source_map: false,
     }
   }
 }
 
+/// The unrecoverable errors to skip with error recovery before giving up.
+const MAX_FATAL_ERRORS: usize = 16;
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
 pub struct SWC {
   pub specifier: String,
   pub module: Module,
   pub source_map: Rc<SourceMap>,
   pub comments: SingleThreadedComments,
+  /// the recoverable errors collected when parsing with error recovery.
+  pub errors: Vec<Diagnostic>,
+  /// the recoverable errors collected when parsing without error recovery, reported as warnings.
+  pub warnings: Vec<Diagnostic>,
 }
 
 impl SWC {
   /// parse source code.
-  pub fn parse(specifier: &str, source: &str, target: EsVersion, lang: Option<String>) -> Result<Self, anyhow::Error> {
-    let source_map = SourceMap::default();
-    let source_file = source_map.new_source_file(FileName::Real(Path::new(specifier).to_path_buf()), source.into());
-    let sm = &source_map;
-    let error_buffer = ErrorBuffer::new(specifier);
+  /// with `error_recovery`, the recoverable errors are collected in `errors`, otherwise they are reported
+  /// as `warnings`. An unrecoverable error fails the parse unless `error_recovery` is enabled, then the
+  /// line of the error is skipped and the rest of the module is parsed again, the error is collected
+  /// in `errors` as well.
+  pub fn parse(
+    specifier: &str,
+    source: &str,
+    target: EsVersion,
+    lang: Option<String>,
+    error_recovery: bool,
+  ) -> Result<Self, anyhow::Error> {
     let syntax = get_syntax(specifier, lang);
-    let input = StringInput::from(&*source_file);
-    let comments = SingleThreadedComments::default();
-    let lexer = Lexer::new(syntax, target, input, Some(&comments));
-    let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
-    let handler = Handler::with_emitter_and_flags(
-      Box::new(error_buffer.clone()),
-      HandlerFlags {
-        can_emit_warnings: true,
-        dont_buffer_diagnostics: true,
-        ..HandlerFlags::default()
-      },
-    );
-    let module = parser
-      .parse_module()
-      .map_err(move |err| {
+    let mut source = source.to_owned();
+    let mut fatal_errors: Vec<Diagnostic> = vec![];
+    let (module, source_map, comments, errors) = loop {
+      let source_map = SourceMap::default();
+      let source_file = source_map.new_source_file(FileName::Real(Path::new(specifier).to_path_buf()), source.clone());
+      let error_buffer = ErrorBuffer::new(specifier);
+      let input = StringInput::from(&*source_file);
+      let comments = SingleThreadedComments::default();
+      let lexer = Lexer::new(syntax, target, input, Some(&comments));
+      let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
+      let handler = error_buffer.handler();
+      let emit_error = |err: ParserError| {
+        let code = syntax_error_code(err.kind());
         let mut diagnostic = err.into_diagnostic(&handler);
+        diagnostic.code(DiagnosticId::Error(code.into()));
         diagnostic.emit();
-        DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo))
-      })
-      .unwrap();
+      };
+      let module = parser.parse_module();
+      for err in parser.take_errors() {
+        emit_error(err);
+      }
+      match module {
+        Ok(module) => {
+          let DiagnosticBuffer(errors) = DiagnosticBuffer::from_error_buffer(error_buffer, &source_map);
+          break (module, source_map, comments, errors);
+        }
+        Err(err) => {
+          let offset = (err.span().lo.0 - source_file.start_pos.0) as usize;
+          emit_error(err);
+          let DiagnosticBuffer(mut diagnostics) = DiagnosticBuffer::from_error_buffer(error_buffer, &source_map);
+          source = match skip_line(&source, offset) {
+            Some(source) if error_recovery && fatal_errors.len() < MAX_FATAL_ERRORS => source,
+            _ => {
+              fatal_errors.append(&mut diagnostics);
+              return Err(DiagnosticBuffer(fatal_errors).into());
+            }
+          };
+          // the recoverable errors are reported by the next parse
+          fatal_errors.extend(diagnostics.pop());
+        }
+      }
+    };
+    let errors = [fatal_errors, errors].concat();
+    let (errors, warnings) = if error_recovery {
+      (errors, vec![])
+    } else {
+      let warnings = errors
+        .into_iter()
+        .map(|diagnostic| Diagnostic {
+          severity: Severity::Warning,
+          ..diagnostic
+        })
+        .collect();
+      (vec![], warnings)
+    };
 
     Ok(SWC {
       specifier: specifier.into(),
       module,
       source_map: Rc::new(source_map),
       comments,
+      errors,
+      warnings,
     })
   }
 
+  /// get the `"use client"` and `"use server"` directives of the module.
+  pub fn directives(&self) -> Vec<Directive> {
+    get_directives(&self.module.body)
+  }
+
   /// parse deps in the module.
-  pub fn parse_deps(&self, resolver: Rc<RefCell<Resolver>>) -> Result<Vec<DependencyDescriptor>, anyhow::Error> {
+  /// the type-only imports/exports are included with `type_only_deps`.
+  pub fn parse_deps(
+    &self,
+    resolver: Rc<RefCell<Resolver>>,
+    type_only_deps: bool,
+  ) -> Result<Vec<DependencyDescriptor>, anyhow::Error> {
     let program = Program::Module(self.module.clone());
-    let mut resolve_fold = resolve_fold(resolver.clone(), false, true);
-    program.fold_with(&mut resolve_fold);
+    let mut passes = chain!(
+      import_glob(resolver.clone()),
+      resolve_fold(resolver.clone(), None, None, true, type_only_deps)
+    );
+    program.fold_with(&mut passes);
     let resolver = resolver.borrow();
-    Ok(resolver.deps.clone())
+    let deps = resolver
+      .deps
+      .iter()
+      .map(|dep| DependencyDescriptor {
+        loc: dep.span.map(|span| get_location(&self.source_map, span)),
+        ..dep.clone()
+      })
+      .collect();
+    Ok(deps)
   }
 
   /// transform a JS/TS/JSX/TSX file into a JS file, based on the supplied options.
+  /// returns the code, the source map and the warnings.
   pub fn transform(
     self,
     resolver: Rc<RefCell<Resolver>>,
     options: &EmitOptions,
-  ) -> Result<(String, Option<String>), anyhow::Error> {
+  ) -> Result<(String, Option<String>, Vec<Diagnostic>), anyhow::Error> {
     swc_common::GLOBALS.set(&Globals::new(), || {
       let unresolved_mark = Mark::new();
       let top_level_mark = Mark::fresh(Mark::root());
@@ -122,24 +207,11 @@ impl SWC {
       let jsxt = options.jsx.as_deref().unwrap_or("classic");
       let jsx_preserve = jsxt == "preserve";
       let is_jsx = extname == "jsx" || extname == "tsx";
+      let jsx_import_source = options.jsx_import_source.clone().unwrap_or("react".into());
       let react_options = if jsxt == "automatic" {
-        let mut resolver = resolver.borrow_mut();
-        let import_source = options.jsx_import_source.as_deref().unwrap_or("react");
-        let runtime = if is_dev { "/jsx-dev-runtime" } else { "/jsx-runtime" };
-        let import_source = resolver.resolve(&(import_source.to_owned() + runtime), false, None);
-        let import_source = import_source
-          .split("?")
-          .next()
-          .unwrap_or(&import_source)
-          .strip_suffix(runtime)
-          .unwrap_or(&import_source)
-          .to_string();
-        if !is_jsx {
-          resolver.deps.pop();
-        }
         react::Options {
           runtime: Some(react::Runtime::Automatic),
-          import_source: Some(import_source),
+          import_source: Some(jsx_import_source.clone()),
           ..Default::default()
         }
       } else {
@@ -153,7 +225,14 @@ impl SWC {
       let passes = chain!(
         swc_ecma_transforms::resolver(unresolved_mark, top_level_mark, is_ts),
         Optional::new(react::jsx_src(is_dev, self.source_map.clone()), is_jsx && is_dev),
-        resolve_fold(resolver.clone(), options.strip_data_export, false),
+        import_glob(resolver.clone()),
+        resolve_fold(
+          resolver.clone(),
+          options.strip_data_export.clone(),
+          options.rsc.clone(),
+          false,
+          false
+        ),
         decorators::decorators(decorators::Config {
           legacy: true,
           emit_metadata: false,
@@ -260,7 +339,19 @@ impl SWC {
           ),
           is_jsx && !jsx_preserve
         ),
+        Optional::new(
+          jsx_runtime(resolver.clone(), jsx_import_source),
+          is_jsx && !jsx_preserve
+        ),
         Optional::new(hmr(resolver.clone()), is_dev && !specifier_is_remote),
+        Optional::new(
+          chain!(
+            define(&options.define, unresolved_mark),
+            simplify::expr_simplifier(unresolved_mark, Default::default()),
+            simplify::dead_branch_remover(unresolved_mark)
+          ),
+          !options.define.is_empty()
+        ),
         dce::dce(
           dce::Config {
             module_mark: None,
@@ -280,49 +371,30 @@ impl SWC {
           }),
           options.minify.is_some()
         ),
+        // remove dead deps by tree-shaking
+        Optional::new(
+          prune_deps(resolver.clone()),
+          options.strip_data_export.is_some() || !options.define.is_empty()
+        ),
         hygiene::hygiene_with_config(hygiene::Config {
           keep_class_names: true,
-          top_level_mark: top_level_mark,
+          top_level_mark,
           ..Default::default()
         }),
         fixer(Some(&self.comments)),
       );
 
-      let (mut code, map) = self.emit(passes, options).unwrap();
-
-      // remove dead deps by tree-shaking
-      if options.strip_data_export {
-        let mut resolver = resolver.borrow_mut();
-        let mut deps: Vec<DependencyDescriptor> = Vec::new();
-        let a = code.split("\"").collect::<Vec<&str>>();
-        for dep in resolver.deps.clone() {
-          if dep.specifier.ends_with("/jsx-runtime")
-            || dep.specifier.ends_with("/jsx-dev-runtime")
-            || a.contains(&dep.import_url.as_str())
-          {
-            deps.push(dep);
-          }
-        }
-        resolver.deps = deps;
+      let error_buffer = ErrorBuffer::new(&self.specifier);
+      let handler = error_buffer.handler();
+      let (code, map) = HANDLER.set(&handler, || self.emit(passes, options))?;
+      let DiagnosticBuffer(diagnostics) = DiagnosticBuffer::from_error_buffer(error_buffer, &self.source_map);
+      let (errors, warnings): (Vec<Diagnostic>, Vec<Diagnostic>) =
+        diagnostics.into_iter().partition(|d| d.severity == Severity::Error);
+      if !errors.is_empty() {
+        return Err(DiagnosticBuffer(errors).into());
       }
 
-      // resolve jsx-runtime url
-      let mut jsx_runtime = None;
-      let resolver = resolver.borrow();
-      for dep in &resolver.deps {
-        if dep.specifier.ends_with("/jsx-runtime") || dep.specifier.ends_with("/jsx-dev-runtime") {
-          jsx_runtime = Some((dep.specifier.clone(), dep.import_url.clone()));
-          break;
-        }
-      }
-      if let Some((jsx_runtime, import_url)) = jsx_runtime {
-        code = code.replace(
-          format!("\"{}\"", jsx_runtime).as_str(),
-          format!("\"{}\"", import_url).as_str(),
-        );
-      }
-
-      Ok((code, map))
+      Ok((code, map, warnings))
     })
   }
 
@@ -359,7 +431,7 @@ impl SWC {
       let mut buf = Vec::new();
       self
         .source_map
-        .build_source_map_from(&mut src_map_buf, None)
+        .build_source_map_from(&src_map_buf, None)
         .to_writer(&mut buf)
         .unwrap();
       Ok((src, Some(String::from_utf8(buf).unwrap())))
@@ -369,6 +441,34 @@ impl SWC {
   }
 }
 
+/// Replace the line at the offset with spaces, the offsets of the other lines are kept. Returns `None` if
+/// the line is blank already, e.g. an unclosed block at the end of the module.
+fn skip_line(source: &str, offset: usize) -> Option<String> {
+  let start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+  let end = source[offset..].find('\n').map(|i| offset + i).unwrap_or(source.len());
+  if source[start..end].trim().is_empty() {
+    return None;
+  }
+  Some([&source[..start], &" ".repeat(end - start), &source[end..]].concat())
+}
+
+/// get the line/column location of the span.
+pub fn get_location(source_map: &SourceMap, span: Span) -> Location {
+  Location {
+    start: get_position(source_map, span.lo),
+    end: get_position(source_map, span.hi),
+  }
+}
+
+fn get_position(source_map: &SourceMap, pos: BytePos) -> Position {
+  let loc = source_map.lookup_char_pos(pos);
+  Position {
+    line: loc.line,
+    column: loc.col.0,
+    utf16_offset: get_utf16_offset(&loc.file, pos),
+  }
+}
+
 fn get_es_config(jsx: bool) -> EsConfig {
   EsConfig {
     fn_bind: true,
@@ -389,16 +489,16 @@ fn get_ts_config(tsx: bool) -> TsConfig {
   }
 }
 
-fn get_syntax(specifier: &str, lang: Option<String>) -> Syntax {
+pub fn get_syntax(specifier: &str, lang: Option<String>) -> Syntax {
   let lang = if let Some(lang) = lang {
     lang
   } else {
     specifier
-      .split(|c| c == '?' || c == '#')
+      .split(['?', '#'])
       .next()
       .unwrap()
       .split('.')
-      .last()
+      .next_back()
       .unwrap_or("js")
       .to_lowercase()
   };
@@ -1,3 +1,4 @@
+use crate::resolver::DependencyKind;
 use swc_common::DUMMY_SP;
 use swc_ecmascript::ast::*;
 use swc_ecmascript::utils::quote_ident;
@@ -73,6 +74,196 @@ pub fn is_call_expr_by_name(call: &CallExpr, name: &str) -> bool {
   }
 }
 
+/// Check if the expression is `import.meta.{prop}`, e.g. `import.meta.url`.
+pub fn is_import_meta_prop(expr: &Expr, prop: &str) -> bool {
+  match expr {
+    Expr::Member(MemberExpr {
+      obj,
+      prop: MemberProp::Ident(id),
+      ..
+    }) => {
+      id.sym.as_ref() == prop
+        && matches!(
+          obj.as_ref(),
+          Expr::MetaProp(MetaPropExpr {
+            kind: MetaPropKind::ImportMeta,
+            ..
+          })
+        )
+    }
+    _ => false,
+  }
+}
+
+/// Get the asset URL of `new URL("./logo.png", import.meta.url)`.
+pub fn get_new_url_asset(new_expr: &NewExpr) -> Option<&Str> {
+  if !matches!(new_expr.callee.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "URL") {
+    return None;
+  }
+  match new_expr.args.as_deref() {
+    Some(
+      [ExprOrSpread {
+        spread: None,
+        expr: url,
+      }, ExprOrSpread {
+        spread: None,
+        expr: base,
+      }],
+    ) if is_import_meta_prop(base, "url") => match url.as_ref() {
+      Expr::Lit(Lit::Str(s)) => Some(s),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// Get the asset URL of `import.meta.resolve("./logo.png")`.
+pub fn get_import_meta_resolve_asset(call: &CallExpr) -> Option<&Str> {
+  match &call.callee {
+    Callee::Expr(callee) if is_import_meta_prop(callee, "resolve") => match call.args.as_slice() {
+      [ExprOrSpread { spread: None, expr }] => match expr.as_ref() {
+        Expr::Lit(Lit::Str(s)) => Some(s),
+        _ => None,
+      },
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// Check if the expression is the global `name`, or `window.{name}`, `self.{name}` and `globalThis.{name}`.
+fn is_global(expr: &Expr, name: &str) -> bool {
+  match expr {
+    Expr::Ident(id) => id.sym.as_ref() == name,
+    Expr::Member(MemberExpr {
+      obj,
+      prop: MemberProp::Ident(id),
+      ..
+    }) => {
+      id.sym.as_ref() == name
+        && matches!(obj.as_ref(), Expr::Ident(obj) if matches!(obj.sym.as_ref(), "window" | "self" | "globalThis"))
+    }
+    _ => false,
+  }
+}
+
+/// Get the worker kind of `new Worker(...)`, `new SharedWorker(...)` or `new window.Worker(...)`.
+pub fn get_worker_kind(new_expr: &NewExpr) -> Option<DependencyKind> {
+  if is_global(&new_expr.callee, "Worker") {
+    Some(DependencyKind::Worker)
+  } else if is_global(&new_expr.callee, "SharedWorker") {
+    Some(DependencyKind::SharedWorker)
+  } else {
+    None
+  }
+}
+
+/// Check if the call is `navigator.serviceWorker.register(...)`.
+pub fn is_service_worker_register(call: &CallExpr) -> bool {
+  match &call.callee {
+    Callee::Expr(callee) => match callee.as_ref() {
+      Expr::Member(MemberExpr {
+        obj,
+        prop: MemberProp::Ident(register),
+        ..
+      }) if register.sym.as_ref() == "register" => match obj.as_ref() {
+        Expr::Member(MemberExpr {
+          obj,
+          prop: MemberProp::Ident(service_worker),
+          ..
+        }) => service_worker.sym.as_ref() == "serviceWorker" && is_global(obj, "navigator"),
+        _ => false,
+      },
+      _ => false,
+    },
+    _ => false,
+  }
+}
+
+/// Get the URL of a worker entry, `"./worker.ts"` or `new URL("./worker.ts", import.meta.url)`.
+pub fn get_entry_url(expr: &Expr) -> Option<&Str> {
+  match expr {
+    Expr::Lit(Lit::Str(s)) => Some(s),
+    Expr::New(new_expr) => get_new_url_asset(new_expr),
+    _ => None,
+  }
+}
+
+/// The mutable version of `get_entry_url`.
+pub fn get_entry_url_mut(expr: &mut Expr) -> Option<&mut Str> {
+  get_entry_url(expr)?;
+  match expr {
+    Expr::Lit(Lit::Str(s)) => Some(s),
+    Expr::New(new_expr) => match new_expr.args.as_mut()?[0].expr.as_mut() {
+      Expr::Lit(Lit::Str(s)) => Some(s),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// Get the static parts of a computed specifier, e.g. `./locales/${lang}.ts` or `"./locales/" + lang + ".ts"`
+/// -> `["./locales/", ".ts"]`. Returns `None` if there is no static prefix or no dynamic part.
+pub fn get_glob_parts(expr: &Expr) -> Option<Vec<String>> {
+  let parts = match expr {
+    Expr::Tpl(Tpl { quasis, .. }) => quasis
+      .iter()
+      .map(|quasi| quasi.cooked.as_ref().unwrap_or(&quasi.raw).to_string())
+      .collect(),
+    Expr::Bin(BinExpr { op: BinaryOp::Add, .. }) => {
+      let mut parts: Vec<String> = vec![String::new()];
+      let mut dynamic = false;
+      flatten_concat(expr, &mut |part| match part {
+        Some(s) => {
+          dynamic = false;
+          parts.last_mut().unwrap().push_str(s);
+        }
+        None if !dynamic => {
+          dynamic = true;
+          parts.push(String::new());
+        }
+        None => {}
+      });
+      parts
+    }
+    _ => return None,
+  };
+  if parts.len() < 2 || parts[0].is_empty() {
+    return None;
+  }
+  Some(parts)
+}
+
+/// Join the static parts of a computed specifier with `*`, the adjacent dynamic parts are matched by
+/// a single wildcard, e.g. `./${dir}${name}.ts` -> `./*.ts`.
+pub fn join_glob_parts(parts: &[String]) -> String {
+  let mut pattern = String::new();
+  for (i, part) in parts.iter().enumerate() {
+    if i > 0 && !pattern.ends_with('*') {
+      pattern.push('*');
+    }
+    pattern.push_str(part);
+  }
+  pattern
+}
+
+// visit the operands of `a + b + c`, with `Some(s)` for string literals and `None` for the others.
+fn flatten_concat(expr: &Expr, f: &mut dyn FnMut(Option<&str>)) {
+  match expr {
+    Expr::Bin(BinExpr {
+      op: BinaryOp::Add,
+      left,
+      right,
+      ..
+    }) => {
+      flatten_concat(left, f);
+      flatten_concat(right, f);
+    }
+    Expr::Lit(Lit::Str(s)) => f(Some(s.value.as_ref())),
+    _ => f(None),
+  }
+}
+
 pub fn new_str(s: &str) -> Str {
   Str {
     span: DUMMY_SP,
@@ -1,7 +1,13 @@
 use super::*;
+use error::DiagnosticBuffer;
+use exports::{analyze_exports, ExportDescriptor, ExportKind};
 use lightningcss::targets::Browsers;
 use regex::Regex;
+use resolver::DependencyKind;
 use std::collections::HashMap;
+use std::str::FromStr;
+use strip_data_export::{StripDataExport, StripDataExportOption};
+use url::Url;
 
 fn transform(specifer: &str, source: &str, is_dev: bool, options: &EmitOptions) -> (String, Rc<RefCell<Resolver>>) {
   let importmap = import_map::parse_from_json(
@@ -18,7 +24,7 @@ fn transform(specifer: &str, source: &str, is_dev: bool, options: &EmitOptions)
   let mut graph_versions: HashMap<String, String> = HashMap::new();
   graph_versions.insert("./foo.ts".into(), "100".into());
   let module =
-    SWC::parse(specifer, source, swc_ecmascript::ast::EsVersion::Es2022, None).expect("could not parse module");
+    SWC::parse(specifer, source, swc_ecmascript::ast::EsVersion::Es2022, None, false).expect("could not parse module");
   let resolver = Rc::new(RefCell::new(Resolver::new(
     specifer,
     "https://deno.land/x/aleph",
@@ -28,7 +34,7 @@ fn transform(specifer: &str, source: &str, is_dev: bool, options: &EmitOptions)
     true,
     is_dev,
   )));
-  let (code, _) = module.transform(resolver.clone(), options).unwrap();
+  let (code, _, _) = module.transform(resolver.clone(), options).unwrap();
   println!("{}", code);
   (code, resolver)
 }
@@ -110,6 +116,7 @@ fn parcel_css() {
     pseudo_classes: None,
     unused_symbols: None,
     analyze_dependencies: None,
+    error_recovery: None,
     drafts: Some(css::Drafts {
       nesting: true,
       custom_media: true,
@@ -134,9 +141,11 @@ fn import_resolving() {
     setTimeout(() => {
       import("https://esm.sh/asksomeonelse")
       new Worker("https://esm.sh/asksomeonelse")
+      new window.SharedWorker(new URL("./shared.ts", import.meta.url), { type: "module" })
+      navigator.serviceWorker.register("/sw.ts", { scope: "/" })
     }, 1000)
   "#;
-  let (code, _) = transform("./pages/blog/$id.tsx", source, false, &EmitOptions::default());
+  let (code, resolver) = transform("./pages/blog/$id.tsx", source, false, &EmitOptions::default());
   assert!(code.contains("\"/-/esm.sh/react@18\""));
   assert!(code.contains("\"../../foo.ts?v=100\""));
   assert!(code.contains("\"./Layout.tsx?v=1.0.0\""));
@@ -144,6 +153,78 @@ fn import_resolving() {
   assert!(code.contains("\"../../style/app.css?module&v=1.0.0\""));
   assert!(code.contains("import(\"/-/esm.sh/asksomeonelse\")"));
   assert!(code.contains("new Worker(\"/-/esm.sh/asksomeonelse\")"));
+  assert!(code.contains("new window.SharedWorker(new URL(\"./shared.ts?v=1.0.0\", import.meta.url), {"));
+  assert!(code.contains("navigator.serviceWorker.register(\"/sw.ts?v=1.0.0\", {"));
+  let r = resolver.borrow();
+  let kinds: Vec<DependencyKind> = r.deps.iter().rev().take(2).map(|dep| dep.kind).collect();
+  assert_eq!(kinds, vec![DependencyKind::ServiceWorker, DependencyKind::SharedWorker]);
+}
+
+#[test]
+fn worker_document_entries() {
+  let source = r#"
+    new Worker("./worker.ts")
+    new SharedWorker("./shared.ts")
+    new SharedWorker(new URL("./shared.ts", import.meta.url))
+    navigator.serviceWorker.register("../sw.ts", { scope: "/" })
+    navigator.serviceWorker.register(new URL("../sw.ts", import.meta.url))
+  "#;
+  let specifier = "./pages/blog/index.tsx";
+  let (code, resolver) = transform(specifier, source, false, &EmitOptions::default());
+  // the string entries of shared workers and service workers are resolved against the document URL
+  assert!(code.contains("new Worker(\"./worker.ts?v=1.0.0\")"));
+  assert!(code.contains("new SharedWorker(\"/pages/blog/shared.ts?v=1.0.0\")"));
+  assert!(code.contains("new SharedWorker(new URL(\"./shared.ts?v=1.0.0\", import.meta.url))"));
+  assert!(code.contains("navigator.serviceWorker.register(\"/pages/sw.ts?v=1.0.0\", {"));
+  assert!(code.contains("navigator.serviceWorker.register(new URL(\"../sw.ts?v=1.0.0\", import.meta.url))"));
+  let import_urls: Vec<String> = resolver
+    .borrow()
+    .deps
+    .iter()
+    .map(|dep| dep.import_url.clone())
+    .collect();
+  assert_eq!(
+    import_urls,
+    vec![
+      "./worker.ts?v=1.0.0",
+      "/pages/blog/shared.ts?v=1.0.0",
+      "./shared.ts?v=1.0.0",
+      "/pages/sw.ts?v=1.0.0",
+      "../sw.ts?v=1.0.0",
+    ]
+  );
+
+  // the lexer-only scanner resolves the entries the same way
+  let module = SWC::parse(specifier, source, EsVersion::Es2022, None, false).unwrap();
+  let deps = module.parse_deps(new_deps_resolver(specifier), false).unwrap();
+  let scanned_deps = scanner::scan_deps(specifier, source, None, new_deps_resolver(specifier), false).unwrap();
+  assert_eq!(deps[1].import_url, "/pages/blog/shared.ts");
+  assert_eq!(deps[3].import_url, "/pages/sw.ts");
+  assert_eq!(scanned_deps, deps);
+}
+
+#[test]
+fn asset_resolving() {
+  let source = r#"
+    const logo = new URL("./logo.png", import.meta.url)
+    const font = new URL("~/fonts/inter.woff2", import.meta.url).href
+    const style = import.meta.resolve("../../style/app.css")
+    const icon = new URL("https://esm.sh/icons/home.svg", import.meta.url)
+    const other = new URL("./other.png", location.href)
+  "#;
+  let (code, resolver) = transform("./pages/blog/$id.tsx", source, false, &EmitOptions::default());
+  assert!(code.contains("new URL(\"./logo.png?v=1.0.0\", import.meta.url)"));
+  assert!(code.contains("new URL(\"../../fonts/inter.woff2?v=1.0.0\", import.meta.url)"));
+  assert!(code.contains("import.meta.resolve(\"../../style/app.css?v=1.0.0\")"));
+  assert!(code.contains("new URL(\"/-/esm.sh/icons/home.svg\", import.meta.url)"));
+  assert!(code.contains("new URL(\"./other.png\", location.href)"));
+  let r = resolver.borrow();
+  assert_eq!(r.deps.len(), 4);
+  assert!(r
+    .deps
+    .iter()
+    .all(|dep| dep.kind == DependencyKind::Asset && dep.dynamic));
+  assert_eq!(r.deps[1].specifier, "./fonts/inter.woff2");
 }
 
 #[test]
@@ -223,11 +304,64 @@ fn jsx_automtic() {
   assert!(code.contains("_jsx(\"h1\", {"));
   assert!(code.contains("children: \"Hello world!\""));
   assert_eq!(
-    resolver.borrow().deps.get(0).unwrap().specifier,
+    resolver.borrow().deps.first().unwrap().specifier,
     "https://esm.sh/react@18/jsx-runtime"
   );
 }
 
+#[test]
+fn jsx_runtime_import() {
+  let source = r#"
+    const runtime = "https://esm.sh/react@18/jsx-dev-runtime"
+    export default function App() {
+      return <h1 title={runtime}>Hello world!</h1>
+    }
+  "#;
+  let (code, resolver) = transform(
+    "./app.tsx",
+    source,
+    true,
+    &EmitOptions {
+      jsx: Some("automatic".into()),
+      jsx_import_source: Some("https://esm.sh/react@18".to_owned()),
+      ..Default::default()
+    },
+  );
+  assert!(code.contains("from \"/-/esm.sh/react@18/jsx-dev-runtime?dev\""));
+  assert!(code.contains("const runtime = \"https://esm.sh/react@18/jsx-dev-runtime\""));
+  let resolver = resolver.borrow();
+  assert_eq!(resolver.deps.len(), 1);
+  assert_eq!(
+    resolver.deps[0].specifier,
+    "https://esm.sh/react@18/jsx-dev-runtime?dev"
+  );
+  assert_eq!(resolver.deps[0].kind, DependencyKind::Import);
+  assert_eq!(resolver.deps[0].names, vec!["jsxDEV"]);
+}
+
+#[test]
+fn jsx_create_element_import_source() {
+  // swc falls back to `createElement` for the elements with both spread props and a key
+  let source = r#"
+    export default function App(props) {
+      return <div {...props} key="app" />
+    }
+  "#;
+  let (code, resolver) = transform(
+    "./app.jsx",
+    source,
+    false,
+    &EmitOptions {
+      jsx: Some("automatic".into()),
+      jsx_import_source: Some("https://esm.sh/preact@10".to_owned()),
+      ..Default::default()
+    },
+  );
+  assert!(code.contains("import { createElement as _createElement } from \"/-/esm.sh/preact@10\""));
+  let deps: Vec<String> = resolver.borrow().deps.iter().map(|dep| dep.specifier.clone()).collect();
+  assert_eq!(deps, vec!["https://esm.sh/preact@10"]);
+}
+
 #[test]
 fn react_refresh() {
   let source = r#"
@@ -315,7 +449,7 @@ fn strip_data_export() {
     source,
     false,
     &EmitOptions {
-      strip_data_export: true,
+      strip_data_export: Some(StripDataExport::default()),
       jsx: Some("automatic".into()),
       jsx_import_source: Some("https://esm.sh/react@18".to_owned()),
       ..Default::default()
@@ -338,3 +472,1330 @@ fn strip_data_export() {
   assert!(!code.contains("const count = 0"));
   assert_eq!(r.borrow().deps.len(), 1);
 }
+
+#[test]
+fn parse_error_diagnostics() {
+  let source = "const a = 1;\nconst b = ;\n";
+  let err = SWC::parse("./mod.ts", source, swc_ecmascript::ast::EsVersion::Es2022, None, false)
+    .err()
+    .expect("should fail to parse");
+  let DiagnosticBuffer(diagnostics) = err.downcast_ref::<DiagnosticBuffer>().unwrap();
+  assert_eq!(diagnostics.len(), 1);
+  let diagnostic = &diagnostics[0];
+  assert_eq!(diagnostic.specifier, "./mod.ts");
+  assert_eq!(diagnostic.severity, error::Severity::Error);
+  assert_eq!(diagnostic.code.as_deref(), Some("TS1109"));
+  assert_eq!(diagnostic.line, Some(2));
+  assert_eq!(diagnostic.column, Some(10));
+  assert_eq!(diagnostic.span.unwrap().start, 23);
+  assert!(err.to_string().ends_with(" at ./mod.ts:2:10"));
+  assert_eq!(
+    diagnostic.code_frame.as_deref(),
+    Some("  1 | const a = 1;\n> 2 | const b = ;\n    |           ^")
+  );
+
+  // the column is counted in characters and the span in UTF-16 code units, the caret is under the
+  // wide characters
+  let source = "const s = '日本'; const b = ;\n";
+  let err = SWC::parse("./mod.ts", source, swc_ecmascript::ast::EsVersion::Es2022, None, false)
+    .err()
+    .expect("should fail to parse");
+  let DiagnosticBuffer(diagnostics) = err.downcast_ref::<DiagnosticBuffer>().unwrap();
+  let diagnostic = &diagnostics[0];
+  assert_eq!(diagnostic.column, Some(26));
+  assert_eq!(diagnostic.span.unwrap().start, 26);
+  assert_eq!(source.encode_utf16().nth(26), Some(';' as u16));
+  assert_eq!(
+    diagnostic.code_frame.as_deref(),
+    Some("> 1 | const s = '日本'; const b = ;\n    |                             ^")
+  );
+}
+
+#[test]
+fn transform_warnings() {
+  let source = r#"
+    import { render } from "preact"
+    export { data }
+    export default () => render()
+  "#;
+  let importmap = import_map::parse_from_json(&Url::from_str("file:///").unwrap(), "{}")
+    .unwrap()
+    .import_map;
+  let module = SWC::parse("./app.ts", source, swc_ecmascript::ast::EsVersion::Es2022, None, false).unwrap();
+  let resolver = Rc::new(RefCell::new(Resolver::new(
+    "./app.ts",
+    "https://deno.land/x/aleph",
+    importmap,
+    HashMap::new(),
+    None,
+    false,
+    false,
+  )));
+  let (_, _, warnings) = module
+    .transform(
+      resolver,
+      &EmitOptions {
+        strip_data_export: Some(StripDataExport::default()),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+  assert_eq!(warnings.len(), 2);
+  // the data exports are checked before resolving the imports
+  assert_eq!(warnings[0].code.as_deref(), Some("unstripped-data-export"));
+  assert_eq!(warnings[0].severity, error::Severity::Warning);
+  assert_eq!(warnings[0].line, Some(3));
+  assert_eq!(warnings[1].code.as_deref(), Some("unresolved-import"));
+  assert_eq!(warnings[1].line, Some(2));
+  assert!(warnings[1].message.contains("\"preact\""));
+}
+
+#[test]
+fn parcel_css_warnings() {
+  let cfg = css::Config {
+    targets: None,
+    minify: None,
+    source_map: None,
+    css_modules: None,
+    pseudo_classes: None,
+    unused_symbols: None,
+    analyze_dependencies: None,
+    error_recovery: None,
+    drafts: None,
+  };
+  let res = css::compile(
+    "style.css".into(),
+    ".foo {\n  color: red;\n}\n.bar:hover:unknown { color: red }",
+    &cfg,
+  )
+  .unwrap();
+  assert_eq!(res.warnings.len(), 1);
+  assert_eq!(res.warnings[0].message, "Unsupported pseudo class or element: unknown");
+  assert_eq!(res.warnings[0].severity, error::Severity::Warning);
+  assert_eq!(res.warnings[0].line, Some(4));
+}
+
+#[test]
+fn error_recovery() {
+  let source = r#"
+    const a = 010;
+    export default function App() {
+      return a
+    }
+  "#;
+  // the recoverable errors are reported as warnings without error recovery
+  let module = SWC::parse("./app.js", source, swc_ecmascript::ast::EsVersion::Es2022, None, false).unwrap();
+  assert!(module.errors.is_empty());
+  assert_eq!(module.warnings.len(), 1);
+  assert_eq!(module.warnings[0].severity, error::Severity::Warning);
+
+  let module = SWC::parse("./app.js", source, swc_ecmascript::ast::EsVersion::Es2022, None, true).unwrap();
+  assert!(module.warnings.is_empty());
+  assert_eq!(module.errors.len(), 1);
+  assert_eq!(module.errors[0].severity, error::Severity::Error);
+  assert_eq!(module.errors[0].line, Some(2));
+  let resolver = Rc::new(RefCell::new(Resolver::new(
+    "./app.js",
+    "https://deno.land/x/aleph",
+    import_map::ImportMap::new(Url::from_str("file:///").unwrap()),
+    HashMap::new(),
+    None,
+    false,
+    false,
+  )));
+  let (code, _, _) = module.transform(resolver, &EmitOptions::default()).unwrap();
+  assert!(code.contains("export default function App()"));
+
+  // the lines of the unrecoverable errors are skipped with error recovery
+  let source = "import a from \"./a.ts\"\nconst b = ;\nimport c from \"./c.ts\"\nexport default [a, c]\n";
+  let options = Options {
+    error_recovery: Some(true),
+    ..Default::default()
+  };
+  let output = crate::transform("./r.ts", source, &options).unwrap();
+  assert!(output.code.contains("export default [\n    a,\n    c\n];"));
+  assert!(!output.code.contains("const b"));
+  assert_eq!(output.errors.len(), 1);
+  assert_eq!(output.errors[0].severity, error::Severity::Error);
+  assert_eq!((output.errors[0].line, output.errors[0].column), (Some(2), Some(10)));
+  let deps = crate::parse_deps("./r.ts", source, &options).unwrap();
+  let deps: Vec<&str> = deps.iter().map(|dep| dep.specifier.as_str()).collect();
+  assert_eq!(deps, vec!["./a.ts", "./c.ts"]);
+  let err = crate::transform("./r.ts", source, &Options::default()).err().unwrap();
+  assert_eq!(err.to_string(), "Expression expected at ./r.ts:2:10");
+  // every unrecoverable error is skipped
+  let source = "import a from \"./a.ts\"\nconst b = ;\nconst c = ;\nexport default a\n";
+  let output = crate::transform("./r.ts", source, &options).unwrap();
+  assert!(output.code.contains("export default a"));
+  let lines: Vec<Option<usize>> = output.errors.iter().map(|err| err.line).collect();
+  assert_eq!(lines, vec![Some(2), Some(3)]);
+}
+
+#[test]
+fn parcel_css_error_recovery() {
+  let source = ".foo { color: red }\n.bar { color: green; }}\n.baz { color: blue }";
+  let mut cfg = css::Config {
+    targets: None,
+    minify: Some(true),
+    source_map: None,
+    css_modules: None,
+    pseudo_classes: None,
+    unused_symbols: None,
+    analyze_dependencies: None,
+    error_recovery: None,
+    drafts: None,
+  };
+  assert!(css::compile("style.css".into(), source, &cfg).is_err());
+  cfg.error_recovery = Some(true);
+  let res = css::compile("style.css".into(), source, &cfg).unwrap();
+  assert!(res.code.starts_with(".foo{color:red}.bar{color:green}"));
+  assert_eq!(res.warnings.len(), 1);
+  assert_eq!(res.warnings[0].message, "Invalid empty selector");
+}
+
+#[test]
+fn resolve_hook() {
+  struct Hook;
+  impl ResolveHook for Hook {
+    fn resolve(&self, specifier: &str, referrer: &str) -> Option<String> {
+      assert_eq!(referrer, "./pages/index.tsx");
+      if let Some(path) = specifier.strip_prefix("@/") {
+        return Some(format!("/src/{}", path));
+      }
+      if specifier == "virtual:routes" {
+        return Some("/-/virtual/routes.js".into());
+      }
+      None
+    }
+    fn after_resolve(&self, url: &str, _referrer: &str) -> Option<String> {
+      url
+        .strip_prefix("https://esm.sh/")
+        .map(|path| format!("https://cdn.example.com/{}", path))
+    }
+  }
+
+  let source = r#"
+    import { Button } from "@/components/button.tsx"
+    import routes from "virtual:routes"
+    import React from "react"
+    export default () => <Button routes={routes} />
+  "#;
+  let module = SWC::parse(
+    "./pages/index.tsx",
+    source,
+    swc_ecmascript::ast::EsVersion::Es2022,
+    None,
+    false,
+  )
+  .unwrap();
+  let importmap = import_map::parse_from_json(
+    &Url::from_str("file:///").unwrap(),
+    r#"{ "imports": { "react": "https://esm.sh/react@18" } }"#,
+  )
+  .unwrap()
+  .import_map;
+  let resolver = Rc::new(RefCell::new(Resolver::new(
+    "./pages/index.tsx",
+    "https://deno.land/x/aleph",
+    importmap,
+    HashMap::new(),
+    None,
+    false,
+    false,
+  )));
+  resolver.borrow_mut().hook = Some(Arc::new(Hook));
+  let (code, _, warnings) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+  assert!(code.contains("from \"../src/components/button.tsx\""));
+  assert!(code.contains("from \"../-/virtual/routes.js\""));
+  assert!(code.contains("from \"https://cdn.example.com/react@18\""));
+  assert!(warnings.is_empty());
+}
+
+#[test]
+fn import_map() {
+  let importmap = resolver::parse_import_map(
+    r#"{
+      "imports": {
+        "react": "https://esm.sh/react@18",
+        "~/": "./"
+      },
+      "scopes": {
+        "./legacy/": {
+          "react": "https://esm.sh/react@17"
+        }
+      },
+      "integrity": {
+        "https://esm.sh/react@17": "sha384-abc"
+      }
+    }"#,
+    "https://example.com/app/",
+  )
+  .unwrap();
+  assert!(importmap.diagnostics.is_empty());
+  let source = r#"
+    import React from "react"
+    import Button from "~/components/button.tsx"
+    import { h } from "./utils.ts"
+    export default () => h(Button, { React })
+  "#;
+  let module = SWC::parse(
+    "./legacy/index.tsx",
+    source,
+    swc_ecmascript::ast::EsVersion::Es2022,
+    None,
+    false,
+  )
+  .unwrap();
+  let resolver = Rc::new(RefCell::new(Resolver::new(
+    "./legacy/index.tsx",
+    "https://deno.land/x/aleph",
+    importmap.import_map,
+    HashMap::new(),
+    None,
+    false,
+    false,
+  )));
+  resolver.borrow_mut().integrity = importmap.integrity;
+  let (code, _, _) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+  assert!(code.contains("from \"https://esm.sh/react@17\""));
+  assert!(code.contains("from \"../components/button.tsx\""));
+  assert!(code.contains("from \"./utils.ts\""));
+  let r = resolver.borrow();
+  assert_eq!(r.deps[0].integrity.as_deref(), Some("sha384-abc"));
+  assert_eq!(r.deps[1].specifier, "./components/button.tsx");
+  assert_eq!(r.deps[2].specifier, "./legacy/utils.ts");
+
+  let err = resolver::parse_import_map("{ imports: }", "file:///").err().unwrap();
+  assert!(err.to_string().starts_with("Invalid import map:"));
+  let importmap = resolver::parse_import_map(r#"{ "integrity": [] }"#, "file:///").unwrap();
+  assert_eq!(importmap.diagnostics.len(), 1);
+}
+
+#[test]
+fn cdn_specifiers() {
+  let source = r#"
+    import React from "npm:react@18"
+    import { join } from "jsr:@std/path@1"
+    import { readFile } from "node:fs"
+    import { render } from "https://esm.sh/react-dom@18"
+    export default () => render(React, join, readFile)
+  "#;
+  let module = SWC::parse(
+    "./pages/index.tsx",
+    source,
+    swc_ecmascript::ast::EsVersion::Es2022,
+    None,
+    false,
+  )
+  .unwrap();
+  let resolver = Rc::new(RefCell::new(Resolver::new(
+    "./pages/index.tsx",
+    "https://deno.land/x/aleph",
+    import_map::ImportMap::new(Url::from_str("file:///").unwrap()),
+    HashMap::new(),
+    None,
+    false,
+    true,
+  )));
+  resolver.borrow_mut().cdn_origin = Some("https://esm.sh/".into());
+  let (code, _, warnings) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+  assert!(code.contains("from \"https://esm.sh/react@18?dev\""));
+  assert!(code.contains("from \"https://esm.sh/jsr/@std/path@1?dev\""));
+  assert!(code.contains("from \"https://esm.sh/node/fs.js?dev\""));
+  assert!(code.contains("from \"https://esm.sh/react-dom@18?dev\""));
+  assert!(warnings.is_empty());
+  let r = resolver.borrow();
+  assert_eq!(r.deps[0].specifier, "npm:react@18");
+  assert_eq!(r.deps[1].specifier, "jsr:@std/path@1");
+  assert_eq!(r.deps[2].specifier, "node:fs");
+  assert_eq!(r.deps[3].specifier, "https://esm.sh/react-dom@18?dev");
+}
+
+#[test]
+fn cdn_specifiers_without_cdn_origin() {
+  let source = r#"
+    import React from "npm:react@18"
+    import { join } from "jsr:@std/path@1"
+    import { readFile } from "node:fs"
+    import { bar } from "./bar.ts"
+    export default () => [React, join, readFile, bar]
+  "#;
+  let (code, resolver) = transform("./pages/index.tsx", source, false, &EmitOptions::default());
+  assert!(code.contains("from \"npm:react@18\""));
+  assert!(code.contains("from \"jsr:@std/path@1\""));
+  assert!(code.contains("from \"node:fs\""));
+  assert!(code.contains("from \"./bar.ts?v=1.0.0\""));
+  let r = resolver.borrow();
+  assert_eq!(r.deps[0].import_url, "npm:react@18");
+  assert_eq!(r.deps[1].import_url, "jsr:@std/path@1");
+  assert_eq!(r.deps[2].import_url, "node:fs");
+}
+
+#[test]
+fn dependency_kinds() {
+  let source = r#"
+    import React, { useState as useS, type FC } from "react"
+    import * as path from "https://deno.land/std/path/mod.ts"
+    import type { Props } from "./types.ts"
+    import config from "./config.json" assert { type: "json" }
+    import "./style.css"
+    export { default as Button, type ButtonProps } from "./button.tsx"
+    export * from "./utils.ts"
+    const data = import("./data.json", { assert: { type: "json" } })
+    const worker = new Worker("./worker.ts")
+    const logo = new URL("./logo.png", import.meta.url)
+    const shared = new SharedWorker(new URL("./shared.ts", import.meta.url), { type: "module" })
+    navigator.serviceWorker.register("./sw.ts")
+  "#;
+  let module = SWC::parse(
+    "./pages/index.tsx",
+    source,
+    swc_ecmascript::ast::EsVersion::Es2022,
+    None,
+    false,
+  )
+  .unwrap();
+  let resolver = Rc::new(RefCell::new(Resolver::new(
+    "./pages/index.tsx",
+    "",
+    import_map::ImportMap::new(Url::from_str("file:///").unwrap()),
+    HashMap::new(),
+    None,
+    false,
+    false,
+  )));
+  // the type-only deps are skipped by default
+  let deps = module.parse_deps(resolver.clone(), false).unwrap();
+  assert_eq!(deps.len(), 11);
+  assert!(deps.iter().all(|dep| !dep.type_only));
+  resolver.borrow_mut().deps.clear();
+  let deps = module.parse_deps(resolver, true).unwrap();
+  let summary: Vec<(DependencyKind, Vec<String>, bool, bool)> = deps
+    .iter()
+    .map(|dep| (dep.kind, dep.names.clone(), dep.type_only, dep.dynamic))
+    .collect();
+  assert_eq!(
+    summary,
+    vec![
+      (
+        DependencyKind::Import,
+        vec!["default".into(), "useState".into()],
+        false,
+        false
+      ),
+      (DependencyKind::Import, vec!["*".into()], false, false),
+      (DependencyKind::Import, vec!["Props".into()], true, false),
+      (DependencyKind::Import, vec!["default".into()], false, false),
+      (DependencyKind::SideEffect, vec![], false, false),
+      (DependencyKind::ReExport, vec!["default".into()], false, false),
+      (DependencyKind::ExportAll, vec![], false, false),
+      (DependencyKind::DynamicImport, vec![], false, true),
+      (DependencyKind::Worker, vec![], false, true),
+      (DependencyKind::Asset, vec![], false, true),
+      (DependencyKind::SharedWorker, vec![], false, true),
+      (DependencyKind::ServiceWorker, vec![], false, true),
+    ]
+  );
+  assert_eq!(deps[3].attributes.get("type").map(|v| v.as_str()), Some("json"));
+  assert_eq!(deps[7].attributes.get("type").map(|v| v.as_str()), Some("json"));
+}
+
+#[test]
+fn dependency_locations() {
+  let source = "// 😀 emoji\nimport React from \"react\"\n  export * from './ä.ts'\n";
+  let module = SWC::parse("./mod.ts", source, swc_ecmascript::ast::EsVersion::Es2022, None, false).unwrap();
+  let resolver = Rc::new(RefCell::new(Resolver::new(
+    "./mod.ts",
+    "",
+    import_map::ImportMap::new(Url::from_str("file:///").unwrap()),
+    HashMap::new(),
+    None,
+    false,
+    false,
+  )));
+  let deps = module.parse_deps(resolver, false).unwrap();
+  let loc = deps[0].loc.unwrap();
+  assert_eq!((loc.start.line, loc.start.column), (2, 18));
+  assert_eq!((loc.end.line, loc.end.column), (2, 25));
+  // the emoji is 2 UTF-16 code units
+  assert_eq!(loc.start.utf16_offset, 12 + 18);
+  let loc = deps[1].loc.unwrap();
+  assert_eq!((loc.start.line, loc.start.column), (3, 16));
+  assert_eq!((loc.end.line, loc.end.column), (3, 24));
+  assert_eq!(loc.end.utf16_offset - loc.start.utf16_offset, 8);
+}
+
+fn new_deps_resolver(specifier: &str) -> Rc<RefCell<Resolver>> {
+  Rc::new(RefCell::new(Resolver::new(
+    specifier,
+    "",
+    import_map::ImportMap::new(Url::from_str("file:///").unwrap()),
+    HashMap::new(),
+    None,
+    false,
+    false,
+  )))
+}
+
+const SCAN_DEPS_SOURCE: &str = r#"
+  import React, { useState as useS, type FC } from "react"
+  import * as path from "https://deno.land/std/path/mod.ts"
+  import type { Props } from "./types.ts"
+  import type Foo, { Bar } from "./foo.ts"
+  import config from "./config.json" assert { type: "json" }
+  import "./style.css"
+  export { default as Button, type ButtonProps } from "./button.tsx"
+  export * from "./utils.ts"
+  export * as helpers from "./helpers.ts"
+  export type { Theme } from "./theme.ts"
+  export const url = import.meta.url
+  const re = /import("\.\/not-a-dep\.ts")/g
+  const str = `import("./not-a-dep.ts") ${useS}`
+  const data = import("./data.json", { assert: { type: "json" } })
+  const page = import(`./pages/${name}.tsx`)
+  const locale = import("./locales/" + lang.toLowerCase() + ".ts", { assert: { type: "json" } })
+  const notGlob = import(base + "/mod.ts")
+  const worker = new Worker("./worker.ts")
+  const logo = new URL("./logo.png", import.meta.url)
+  const wasm = import.meta.resolve("./app.wasm")
+  const other = new URL("./not-a-dep.png", location.href)
+  const worker2 = new window.Worker(new URL("./worker2.ts", import.meta.url), { type: "module" })
+  const shared = new SharedWorker("./shared.ts")
+  window.navigator.serviceWorker.register(new URL("./sw.ts", import.meta.url), { scope: "/" })
+  foo.navigator.serviceWorker.register("./not-a-dep.ts")
+  export default function App(props: Props) {
+    return <div>{props.children}</div>
+  }
+"#;
+
+#[test]
+fn lexer_only_scan_deps() {
+  let specifier = "./pages/index.tsx";
+  let module = SWC::parse(
+    specifier,
+    SCAN_DEPS_SOURCE,
+    swc_ecmascript::ast::EsVersion::Es2022,
+    None,
+    false,
+  )
+  .unwrap();
+  let deps = module.parse_deps(new_deps_resolver(specifier), true).unwrap();
+  let scanned_deps = scanner::scan_deps(specifier, SCAN_DEPS_SOURCE, None, new_deps_resolver(specifier), true).unwrap();
+  assert_eq!(deps.len(), 19);
+  assert_eq!(scanned_deps, deps);
+  let deps = module.parse_deps(new_deps_resolver(specifier), false).unwrap();
+  let scanned_deps =
+    scanner::scan_deps(specifier, SCAN_DEPS_SOURCE, None, new_deps_resolver(specifier), false).unwrap();
+  assert_eq!(deps.len(), 16);
+  assert_eq!(scanned_deps, deps);
+}
+
+#[test]
+#[ignore = "benchmark, run with `cargo test --release -- --ignored --nocapture`"]
+fn lexer_only_scan_deps_benchmark() {
+  let specifier = "./pages/index.tsx";
+  // a module with some code besides the imports/exports
+  let code = r#"
+    function useCounter(initial: number = 0): [number, () => void] {
+      const [count, setCount] = useS(initial);
+      const inc = () => setCount((n: number) => n + 1);
+      for (let i = 0; i < 10; i++) {
+        if (i % 2 === 0 && count / 2 > i) console.log({ i, count, even: true });
+      }
+      return [count, inc];
+    }
+  "#;
+  let source = (SCAN_DEPS_SOURCE.to_owned() + &code.repeat(5))
+    .repeat(1000)
+    .replace("export default ", "");
+  let start = std::time::Instant::now();
+  let module = SWC::parse(specifier, &source, swc_ecmascript::ast::EsVersion::Es2022, None, false).unwrap();
+  let deps = module.parse_deps(new_deps_resolver(specifier), true).unwrap();
+  let parse_time = start.elapsed();
+  let start = std::time::Instant::now();
+  let scanned_deps = scanner::scan_deps(specifier, &source, None, new_deps_resolver(specifier), true).unwrap();
+  let scan_time = start.elapsed();
+  assert_eq!(scanned_deps.len(), deps.len());
+  println!(
+    "parse_deps: {:?}, scan_deps: {:?} ({:.1}x faster)",
+    parse_time,
+    scan_time,
+    parse_time.as_secs_f64() / scan_time.as_secs_f64()
+  );
+}
+
+#[test]
+fn module_exports() {
+  let source = r#"
+    import { useData } from "aleph/react"
+    export const data = { get: () => ({}) }, config = { ssr: true }
+    export const { a, b: [c] } = { a: 1, b: [2] }
+    export async function GET() {}
+    export const POST = async (req: Request) => new Response()
+    export class Store {}
+    export enum Color { Red }
+    export type Props = {}
+    export interface State {}
+    function Header() {}
+    let count = 0
+    export { Header, count as counter, useData }
+    export { default as Layout, type LayoutProps } from "./layout.tsx"
+    export * from "./utils.ts"
+    export * as helpers from "./helpers.ts"
+    export default async function App() {}
+  "#;
+  let module = SWC::parse("./routes/index.tsx", source, EsVersion::Es2022, None, false).unwrap();
+  let exports: Vec<(String, ExportKind, bool, Option<String>)> = analyze_exports(&module.module)
+    .into_iter()
+    .map(
+      |ExportDescriptor {
+         name,
+         kind,
+         is_async,
+         source,
+       }| (name, kind, is_async, source),
+    )
+    .collect();
+  assert_eq!(
+    exports,
+    vec![
+      ("data".into(), ExportKind::Const, false, None),
+      ("config".into(), ExportKind::Const, false, None),
+      ("a".into(), ExportKind::Const, false, None),
+      ("c".into(), ExportKind::Const, false, None),
+      ("GET".into(), ExportKind::Function, true, None),
+      ("POST".into(), ExportKind::Const, true, None),
+      ("Store".into(), ExportKind::Class, false, None),
+      ("Color".into(), ExportKind::Enum, false, None),
+      ("Header".into(), ExportKind::Function, false, None),
+      ("counter".into(), ExportKind::Let, false, None),
+      ("useData".into(), ExportKind::Value, false, None),
+      (
+        "Layout".into(),
+        ExportKind::ReExport,
+        false,
+        Some("./layout.tsx".into())
+      ),
+      ("*".into(), ExportKind::ReExport, false, Some("./utils.ts".into())),
+      (
+        "helpers".into(),
+        ExportKind::ReExport,
+        false,
+        Some("./helpers.ts".into())
+      ),
+      ("default".into(), ExportKind::Function, true, None),
+    ]
+  );
+}
+
+#[test]
+fn strip_data_export_names() {
+  let source = r#"
+    import { db } from "./db.ts"
+    import { action as formAction } from "./action.ts"
+    const loader = async () => db.query()
+    export { loader, formAction as action }
+    export const { HEAD, config: [config], ...rest } = db.handlers
+    export { OPTIONS, Page } from "./handlers.ts"
+    export default {
+      GET() { return db.get() },
+      options: { OPTIONS: () => db.options() },
+      Component: () => null,
+    }
+  "#;
+  let option: StripDataExportOption =
+    serde_json::from_str(r#"{ "names": ["loader", "action"], "pattern": "^[A-Z]+$|^config$" }"#).unwrap();
+  let (code, r) = transform(
+    "./route.ts",
+    source,
+    false,
+    &EmitOptions {
+      strip_data_export: option.into_config().unwrap(),
+      ..Default::default()
+    },
+  );
+  assert!(code.contains("const loader = true"));
+  assert!(code.contains("const formAction = true"));
+  assert!(code.contains("export { loader, formAction as action }"));
+  // the stripped keys are kept out of `rest`
+  assert!(
+    code.contains("export const { HEAD: _HEAD , config: _config , ...rest } = db.handlers, HEAD = true, config = true")
+  );
+  assert!(code.contains("export { Page } from \"./handlers.ts?v=1.0.0\""));
+  assert!(code.contains("export const OPTIONS = true"));
+  assert!(code.contains("GET: true,"));
+  assert!(code.contains("options: {"));
+  assert!(code.contains("Component: ()=>null"));
+  let deps: Vec<String> = r.borrow().deps.iter().map(|dep| dep.specifier.clone()).collect();
+  assert_eq!(deps, vec!["./db.ts", "./handlers.ts"]);
+
+  let option: StripDataExportOption = serde_json::from_str(r#"["loader"]"#).unwrap();
+  assert_eq!(option.into_config().unwrap().unwrap().names, vec!["loader"]);
+  let option: StripDataExportOption = serde_json::from_str("false").unwrap();
+  assert!(option.into_config().unwrap().is_none());
+  let option: StripDataExportOption = serde_json::from_str(r#"{ "pattern": "(" }"#).unwrap();
+  assert!(option.into_config().is_err());
+}
+
+#[test]
+fn strip_data_export_stubs() {
+  let source = r#"
+    export const data = { defer: true, get: () => fetch("/api") }
+    export const GET = { handler: () => fetch("/api") }
+    export default {
+      mutation: { post: () => fetch("/api", { method: "POST" }) },
+      POST: { handler: () => fetch("/api", { method: "POST" }) },
+    }
+  "#;
+  let (code, _) = transform(
+    "./route.ts",
+    source,
+    false,
+    &EmitOptions {
+      strip_data_export: Some(StripDataExport::default()),
+      ..Default::default()
+    },
+  );
+  // only the `data` and `mutation` objects keep their keys
+  assert!(code.contains("defer: true,\n    get: true\n}"));
+  assert!(code.contains("export const GET = true"));
+  assert!(code.contains("post: true"));
+  assert!(code.contains("POST: true"));
+  assert!(!code.contains("handler"));
+}
+
+#[test]
+fn strip_data_export_prune_deps() {
+  let source = r#"
+    import { json } from "./helper.ts"
+    import { db } from "./db.ts"
+    import { Button } from "./button.tsx"
+    export const data = {
+      get: () => json(db.query()),
+    }
+    export default function App() {
+      console.log("./helper.ts?v=1.0.0")
+      import("./db.ts")
+      return Button()
+    }
+  "#;
+  let (code, r) = transform(
+    "./app.tsx",
+    source,
+    false,
+    &EmitOptions {
+      strip_data_export: Some(StripDataExport::default()),
+      minify: Some(minifier::MinifierOptions { compress: Some(true) }),
+      ..Default::default()
+    },
+  );
+  assert!(!code.contains("import{json}"));
+  let deps: Vec<(String, DependencyKind)> = r
+    .borrow()
+    .deps
+    .iter()
+    .map(|dep| (dep.specifier.clone(), dep.kind))
+    .collect();
+  assert_eq!(
+    deps,
+    vec![
+      ("./button.tsx".into(), DependencyKind::Import),
+      ("./db.ts".into(), DependencyKind::DynamicImport),
+    ]
+  );
+}
+
+#[test]
+fn strip_data_export_keeps_jsx_runtime() {
+  let source = r#"
+    export const data = {
+      get: () => ({ title: "Hello world!" }),
+    }
+    export default function App() {
+      return <h1>Hello world!</h1>
+    }
+  "#;
+  let (_, r) = transform(
+    "./app.tsx",
+    source,
+    true,
+    &EmitOptions {
+      jsx: Some("automatic".into()),
+      jsx_import_source: Some("https://esm.sh/react@18".to_owned()),
+      strip_data_export: Some(StripDataExport::default()),
+      ..Default::default()
+    },
+  );
+  let deps: Vec<String> = r.borrow().deps.iter().map(|dep| dep.specifier.clone()).collect();
+  assert_eq!(deps, vec!["https://esm.sh/react@18/jsx-dev-runtime?dev"]);
+}
+
+#[test]
+fn prune_deps_keeps_dynamic_globs() {
+  let source = r#"
+    export const data = {
+      get: (lang: string) => import(`./data/${lang}.json`),
+    }
+    export default function App({ lang, name }: { lang: string; name: string }) {
+      if (__DEV__) {
+        import(`./dev/${name}.ts`)
+      }
+      return Promise.all([import(`./locales/${lang}.ts`), import("./icons/" + name + ".svg")])
+    }
+  "#;
+  let options = [
+    EmitOptions {
+      strip_data_export: Some(StripDataExport::default()),
+      ..Default::default()
+    },
+    EmitOptions {
+      define: HashMap::from([("__DEV__".to_owned(), serde_json::json!(false))]),
+      ..Default::default()
+    },
+  ];
+  for options in &options {
+    let (_, r) = transform("./app.tsx", source, false, options);
+    let deps: Vec<(String, DependencyKind)> = r
+      .borrow()
+      .deps
+      .iter()
+      .map(|dep| (dep.import_url.clone(), dep.kind))
+      .collect();
+    let mut expected = vec![
+      ("./locales/*.ts".to_owned(), DependencyKind::DynamicGlob),
+      ("./icons/*.svg".to_owned(), DependencyKind::DynamicGlob),
+    ];
+    if options.strip_data_export.is_some() {
+      expected.insert(0, ("./dev/*.ts".into(), DependencyKind::DynamicGlob));
+    } else {
+      expected.insert(0, ("./data/*.json".into(), DependencyKind::DynamicGlob));
+    }
+    assert_eq!(deps, expected);
+  }
+}
+
+#[test]
+fn rsc_directives() {
+  let client_source = r#"
+    "use client"
+    import { useState } from "react"
+    export function Counter() {
+      const [count, setCount] = useState(0)
+      return <button onClick={() => setCount(count + 1)}>{count}</button>
+    }
+    const Button = () => <button />
+    export { Button as "my-button" }
+    export default Counter
+  "#;
+  let server_source = r#"
+    'use server'
+    import { db } from "./db.ts"
+    export async function createTodo(title: string) {
+      await db.insert({ title })
+    }
+  "#;
+  let module = SWC::parse("./counter.tsx", client_source, EsVersion::Es2022, None, false).unwrap();
+  assert_eq!(module.directives(), vec![Directive::UseClient]);
+  let module = SWC::parse("./actions.ts", server_source, EsVersion::Es2022, None, false).unwrap();
+  assert_eq!(module.directives(), vec![Directive::UseServer]);
+
+  let options = EmitOptions {
+    rsc: Some(RscConfig {
+      mode: RscMode::Server,
+      runtime: "https://esm.sh/rsc-runtime@1".into(),
+    }),
+    jsx: Some("automatic".into()),
+    jsx_import_source: Some("https://esm.sh/react@18".to_owned()),
+    ..Default::default()
+  };
+  let (code, resolver) = transform("./counter.tsx", client_source, false, &options);
+  assert!(code.contains("import { registerClientReference } from \"/-/esm.sh/rsc-runtime@1\""));
+  assert!(code.contains("export const Counter = registerClientReference(\"./counter.tsx\", \"Counter\")"));
+  assert!(code.contains("const _ref1 = registerClientReference(\"./counter.tsx\", \"my-button\")"));
+  assert!(code.contains("export { _ref1 as \"my-button\" }"));
+  assert!(code.contains("export default registerClientReference(\"./counter.tsx\", \"default\")"));
+  assert!(!code.contains("useState"));
+  assert_eq!(resolver.borrow().deps.len(), 1);
+  let (code, _) = transform("./actions.ts", server_source, false, &options);
+  assert!(code.contains("export async function createTodo"));
+
+  let options = EmitOptions {
+    rsc: Some(RscConfig {
+      mode: RscMode::Client,
+      runtime: "https://esm.sh/rsc-runtime@1".into(),
+    }),
+    ..Default::default()
+  };
+  let (code, resolver) = transform("./actions.ts", server_source, false, &options);
+  assert!(code.contains("import { createServerReference } from \"/-/esm.sh/rsc-runtime@1\""));
+  assert!(code.contains("export const createTodo = createServerReference(\"./actions.ts\", \"createTodo\")"));
+  assert!(!code.contains("db.ts"));
+  assert_eq!(resolver.borrow().deps.len(), 1);
+
+  let options = Options {
+    rsc: Some(RscMode::Client),
+    ..Default::default()
+  };
+  assert!(matches!(
+    crate::transform("./actions.ts", server_source, &options),
+    Err(Error::InvalidOptions(_))
+  ));
+}
+
+#[test]
+fn define() {
+  let source = r#"
+    import { setupDevtools } from "./devtools.ts"
+    import { render } from "./render.ts"
+    if (process.env.NODE_ENV !== "production") {
+      setupDevtools()
+    }
+    if (__DEV__) {
+      console.log("dev mode")
+    }
+    function log(__DEV__: boolean) {
+      if (__DEV__) console.log(import.meta.env.MODE)
+    }
+    log(true)
+    render({ __DEV__, env: import.meta.env })
+  "#;
+  let mut define = HashMap::new();
+  define.insert("process.env.NODE_ENV".to_owned(), serde_json::json!("production"));
+  define.insert(
+    "import.meta.env".to_owned(),
+    serde_json::json!({ "MODE": "production" }),
+  );
+  define.insert("__DEV__".to_owned(), serde_json::json!(false));
+  let (code, resolver) = transform(
+    "./app.ts",
+    source,
+    false,
+    &EmitOptions {
+      define,
+      ..Default::default()
+    },
+  );
+  assert!(!code.contains("setupDevtools"));
+  assert!(!code.contains("dev mode"));
+  assert!(code.contains("if (__DEV__) console.log(\"production\")"));
+  assert!(code.contains("__DEV__: false"));
+  assert!(code.contains("MODE: \"production\""));
+  let resolver = resolver.borrow();
+  assert_eq!(resolver.deps.len(), 1);
+  assert_eq!(resolver.deps[0].specifier, "./render.ts");
+}
+
+#[test]
+fn native_api() {
+  let source = r#"
+    import { useState } from "react"
+    export default function App() {
+      const [count] = useState(0)
+      return <h1>{count}</h1>
+    }
+  "#;
+  let options = Options {
+    import_map: Some(r#"{ "imports": { "react": "https://esm.sh/react@18" } }"#.into()),
+    jsx: Some("automatic".into()),
+    jsx_import_source: Some("https://esm.sh/react@18".into()),
+    ..Default::default()
+  };
+  let output = crate::transform("./app.tsx", source, &options).unwrap();
+  assert!(output
+    .code
+    .contains("import { jsx as _jsx } from \"https://esm.sh/react@18/jsx-runtime\""));
+  assert_eq!(output.deps.len(), 2);
+  let deps = crate::parse_deps("./app.tsx", source, &options).unwrap();
+  assert_eq!(deps[0].import_url, "https://esm.sh/react@18");
+  let exports = crate::analyze_exports("./app.tsx", source, &options).unwrap();
+  assert_eq!(exports[0].kind, ExportKind::Function);
+
+  let err = crate::transform("./app.ts", "const a = ;", &Options::default()).unwrap_err();
+  assert!(matches!(err, Error::Diagnostics(ref diagnostics) if diagnostics.len() == 1));
+  let options = Options {
+    import_map: Some("{".into()),
+    ..Default::default()
+  };
+  let err = crate::transform("./app.ts", source, &options).unwrap_err();
+  assert!(matches!(err, Error::InvalidOptions(_)));
+
+  let res = crate::compile_css("style.css", ".foo { color: red }", &CssConfig::default()).unwrap();
+  assert_eq!(res.code, ".foo {\n  color: red;\n}\n");
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn transform_batch() {
+  let modules: Vec<(String, String)> = (0..32)
+    .map(|i| {
+      let code = format!(
+        "import {{ useState }} from \"react\"\nexport const n{} = useState({})",
+        i, i
+      );
+      (format!("./routes/{}.ts", i), code)
+    })
+    .chain([("./bad.ts".to_owned(), "const a = ;".to_owned())])
+    .collect();
+  let options = Options {
+    import_map: Some(r#"{ "imports": { "react": "https://esm.sh/react@18" } }"#.into()),
+    ..Default::default()
+  };
+  let results = crate::transform_batch(&modules, &options).unwrap();
+  assert_eq!(results.len(), 33);
+  for (i, result) in results.iter().take(32).enumerate() {
+    let output = result.as_ref().unwrap();
+    assert!(output.code.contains(&format!("export const n{} = useState({})", i, i)));
+    assert_eq!(output.deps[0].import_url, "https://esm.sh/react@18");
+  }
+  assert!(matches!(results[32], Err(Error::Diagnostics(_))));
+
+  let options = Options {
+    import_map: Some("{".into()),
+    ..Default::default()
+  };
+  assert!(matches!(
+    crate::transform_batch(&modules, &options),
+    Err(Error::InvalidOptions(_))
+  ));
+}
+
+#[test]
+fn transform_cache() {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  // counts the transforms by the resolved imports
+  #[derive(Default)]
+  struct Counter(AtomicUsize);
+  impl ResolveHook for Counter {
+    fn resolve(&self, _specifier: &str, _referrer: &str) -> Option<String> {
+      self.0.fetch_add(1, Ordering::SeqCst);
+      None
+    }
+  }
+  let counter = Arc::new(Counter::default());
+  let options = Options {
+    import_map: Some(r#"{ "imports": { "react": "https://esm.sh/react@18" } }"#.into()),
+    define: Some(HashMap::from([
+      ("__DEV__".to_owned(), serde_json::Value::Bool(false)),
+      ("__VERSION__".to_owned(), serde_json::Value::String("1.0".into())),
+    ])),
+    resolve_hook: Some(counter.clone()),
+    ..Default::default()
+  };
+  let transforms = || counter.0.load(Ordering::SeqCst);
+  let code = "import React from \"react\"\nexport const v = __VERSION__";
+  let dir = std::env::temp_dir().join(format!("aleph-compiler-cache-{}", std::process::id()));
+  let cache = TransformCache::new(1024 * 1024).with_dir(&dir);
+
+  let output = cache.transform("./app.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 1);
+  assert!(output.code.contains("export const v = \"1.0\""));
+  let hit = cache.transform("./app.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 1);
+  assert_eq!(hit.code, output.code);
+  assert_eq!(hit.deps, output.deps);
+  assert!(cache.size() > 0);
+
+  // changing the code or the options is a miss
+  let size = cache.size();
+  cache.transform("./app.ts", "export const v = 1", &options).unwrap();
+  assert!(cache.size() > size);
+  let options = Options {
+    source_map: Some(true),
+    ..options
+  };
+  let output = cache.transform("./app.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 2);
+  assert!(output.map.is_some());
+
+  // the outputs on disk are shared with a new cache
+  let disk = TransformCache::new(1024 * 1024).with_dir(&dir);
+  let hit = disk.transform("./app.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 2);
+  assert_eq!(hit.code, output.code);
+  assert_eq!(hit.map, output.map);
+  assert_eq!(hit.deps, output.deps);
+
+  cache.invalidate("./app.ts");
+  assert_eq!(cache.size(), 0);
+  disk.transform("./app.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 2);
+  disk.clear();
+  disk.transform("./app.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 3);
+
+  // only the latest output of a specifier is kept on disk
+  let count_files = || {
+    std::fs::read_dir(dir.join("aleph-compiler"))
+      .unwrap()
+      .flat_map(|entry| std::fs::read_dir(entry.unwrap().path()).unwrap())
+      .count()
+  };
+  assert_eq!(count_files(), 1);
+  disk.transform("./app.ts", "export const v = 2", &options).unwrap();
+  assert_eq!(count_files(), 1);
+  disk.transform("./app.tsx", code, &options).unwrap();
+  assert_eq!(count_files(), 2);
+
+  // only the own subdirectory is removed
+  std::fs::write(dir.join("other.txt"), "").unwrap();
+  disk.clear();
+  assert!(!dir.join("aleph-compiler").exists());
+  assert!(dir.join("other.txt").exists());
+  std::fs::remove_dir_all(&dir).unwrap();
+
+  // the least recently used outputs are evicted
+  let cache = TransformCache::new(64);
+  for i in 0..4 {
+    cache
+      .transform(&format!("./{}.ts", i), "export const a = 1", &Options::default())
+      .unwrap();
+  }
+  assert!(cache.size() <= 64);
+  assert!(cache.size() > 0);
+
+  assert!(matches!(
+    cache.transform("./bad.ts", "const a = ;", &Options::default()),
+    Err(Error::Diagnostics(_))
+  ));
+}
+
+#[test]
+fn transform_cache_eviction() {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  #[derive(Default)]
+  struct Counter(AtomicUsize);
+  impl ResolveHook for Counter {
+    fn resolve(&self, _specifier: &str, _referrer: &str) -> Option<String> {
+      self.0.fetch_add(1, Ordering::SeqCst);
+      None
+    }
+  }
+  let counter = Arc::new(Counter::default());
+  let options = Options {
+    resolve_hook: Some(counter.clone()),
+    ..Default::default()
+  };
+  let transforms = || counter.0.load(Ordering::SeqCst);
+  let code = "import React from \"react\"\nexport default React";
+  let size = {
+    let cache = TransformCache::new(1024 * 1024);
+    cache.transform("./a.ts", code, &options).unwrap();
+    cache.size()
+  };
+
+  // holds two outputs, the least recently used one is evicted by the third
+  let cache = TransformCache::new(size * 2 + size / 2);
+  cache.transform("./a.ts", code, &options).unwrap();
+  cache.transform("./b.ts", code, &options).unwrap();
+  cache.transform("./a.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 3);
+  cache.transform("./c.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 4);
+  assert_eq!(cache.size(), size * 2);
+  cache.transform("./a.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 4);
+  cache.transform("./b.ts", code, &options).unwrap();
+  assert_eq!(transforms(), 5);
+}
+
+#[test]
+fn import_glob() {
+  let source = r#"
+    const routes = import.meta.glob("./routes/**/*.tsx")
+    const eager = import.meta.glob(["/pages/routes/*.tsx", "!**/_*.tsx"], { eager: true })
+    const other = import.meta.glob("../lib/*.{ts,tsx}")
+    export { routes, eager, other }
+  "#;
+  let options = Options {
+    glob_files: Some(vec![
+      "./pages/index.tsx".into(),
+      "./pages/routes/index.tsx".into(),
+      "./pages/routes/_app.tsx".into(),
+      "/pages/routes/blog/$id.tsx".into(),
+      "./pages/routes/style.css".into(),
+      "lib/a.ts".into(),
+      "./lib/b.tsx".into(),
+      "./lib/c.js".into(),
+    ]),
+    ..Default::default()
+  };
+  let output = crate::transform("./pages/index.tsx", source, &options).unwrap();
+  let code = output.code;
+  assert!(code.contains("import * as __glob_1_0 from \"./routes/index.tsx\""));
+  assert!(code.contains("\"./routes/_app.tsx\": ()=>import(\"./routes/_app.tsx\")"));
+  assert!(code.contains("\"./routes/blog/$id.tsx\": ()=>import(\"./routes/blog/$id.tsx\")"));
+  assert!(code.contains("\"./routes/index.tsx\": ()=>import(\"./routes/index.tsx\")"));
+  assert!(code.contains("const eager = {\n    \"/pages/routes/index.tsx\": __glob_1_0\n}"));
+  assert!(code.contains("\"../lib/a.ts\": ()=>import(\"../lib/a.ts\")"));
+  assert!(code.contains("\"../lib/b.tsx\": ()=>import(\"../lib/b.tsx\")"));
+  assert!(!code.contains("style.css"));
+  assert!(!code.contains("c.js"));
+  assert!(!code.contains("import.meta.glob"));
+  let deps: Vec<(String, DependencyKind)> = output
+    .deps
+    .iter()
+    .map(|dep| (dep.specifier.clone(), dep.kind))
+    .collect();
+  assert_eq!(deps.len(), 6);
+  assert!(deps.contains(&("./pages/routes/index.tsx".into(), DependencyKind::Import)));
+  assert!(deps.contains(&("./pages/routes/blog/$id.tsx".into(), DependencyKind::DynamicImport)));
+  let parsed_deps = crate::parse_deps("./pages/index.tsx", source, &options).unwrap();
+  assert_eq!(parsed_deps.len(), 6);
+  assert!(parsed_deps.iter().all(|dep| dep.loc.is_some()));
+
+  let output = crate::transform("./pages/index.tsx", source, &Options::default()).unwrap();
+  assert!(output.code.contains("import.meta.glob(\"./routes/**/*.tsx\")"));
+  assert_eq!(output.warnings[0].code.as_deref(), Some("unresolved-import-glob"));
+
+  // the invalid patterns are reported and the calls are kept
+  let source = r#"
+    const a = import.meta.glob("./{a,b")
+    const b = import.meta.glob(["./routes/*.tsx", "!./{a,b"])
+    const c = import(`./{a,b${name}`)
+  "#;
+  let output = crate::transform("./pages/index.tsx", source, &options).unwrap();
+  assert!(output.code.contains("import.meta.glob(\"./{a,b\")"));
+  assert!(output
+    .code
+    .contains("import.meta.glob([\n    \"./routes/*.tsx\",\n    \"!./{a,b\"\n])"));
+  assert!(output.code.contains("import(`./{a,b${name}`)"));
+  let warnings: Vec<&str> = output
+    .warnings
+    .iter()
+    .filter_map(|warning| warning.code.as_deref())
+    .collect();
+  assert_eq!(warnings, vec!["invalid-import-glob", "invalid-import-glob"]);
+}
+
+#[test]
+fn dynamic_glob_import() {
+  let source = r#"
+    export const load = (lang: string, name: string) => Promise.all([
+      import(`./locales/${lang}.ts`),
+      import("~/components/" + name + ".tsx"),
+      import(`${lang}.ts`),
+    ])
+  "#;
+  let output = crate::transform("./pages/index.tsx", source, &Options::default()).unwrap();
+  assert!(output.code.contains("import(`./locales/${lang}.ts`)"));
+  let deps: Vec<(String, String, DependencyKind)> = output
+    .deps
+    .iter()
+    .map(|dep| (dep.specifier.clone(), dep.import_url.clone(), dep.kind))
+    .collect();
+  assert_eq!(
+    deps,
+    vec![
+      (
+        "./pages/locales/*.ts".into(),
+        "./locales/*.ts".into(),
+        DependencyKind::DynamicGlob
+      ),
+      (
+        "~/components/*.tsx".into(),
+        "~/components/*.tsx".into(),
+        DependencyKind::DynamicGlob
+      ),
+    ]
+  );
+  assert_eq!(
+    output.deps[0].glob,
+    Some(GlobPattern {
+      prefix: "./locales/".into(),
+      suffix: ".ts".into(),
+    })
+  );
+
+  // rewrite to a lookup of the glob files
+  let options = Options {
+    import_map: Some(r#"{ "imports": { "~/": "./" } }"#.into()),
+    graph_versions: Some(HashMap::from([("./pages/locales/en.ts".to_owned(), "2".to_owned())])),
+    glob_files: Some(vec![
+      "./pages/locales/en.ts".into(),
+      "./pages/locales/zh.ts".into(),
+      "./pages/locales/README.md".into(),
+    ]),
+    ..Default::default()
+  };
+  let output = crate::transform("./pages/index.tsx", source, &options).unwrap();
+  assert!(output.code.contains(
+    "((m, k)=>m[k] ? m[k]() : import(k))({\n            __proto__: null,\n            \"./locales/en.ts\": ()=>import(\"./locales/en.ts?v=2\"),\n            \"./locales/zh.ts\": ()=>import(\"./locales/zh.ts\")\n        }, `./locales/${lang}.ts`)"
+  ));
+  let deps: Vec<(String, DependencyKind)> = output
+    .deps
+    .iter()
+    .map(|dep| (dep.specifier.clone(), dep.kind))
+    .collect();
+  assert_eq!(
+    deps,
+    vec![
+      ("./pages/locales/en.ts".into(), DependencyKind::DynamicImport),
+      ("./pages/locales/zh.ts".into(), DependencyKind::DynamicImport),
+      ("./components/*.tsx".into(), DependencyKind::DynamicGlob),
+    ]
+  );
+
+  // the keys of `Object.prototype` are not matched by the lookup
+  let options = Options {
+    glob_files: Some(vec!["./pages/locales/constructor.ts".into()]),
+    ..Default::default()
+  };
+  let source = "export const load = (lang: string) => import(`./locales/${lang}.ts`)";
+  let output = crate::transform("./pages/index.tsx", source, &options).unwrap();
+  assert!(output.code.contains(
+    "({\n        __proto__: null,\n        \"./locales/constructor.ts\": ()=>import(\"./locales/constructor.ts\")\n    }"
+  ));
+
+  // the adjacent dynamic parts are matched by a single wildcard
+  let source = r#"
+    export const a = (dir: string, name: string) => import(`./locales/${dir}${name}.ts`)
+    export const b = (dir: string, name: string) => import("./components/" + dir + name + ".tsx")
+  "#;
+  let output = crate::transform("./pages/index.tsx", source, &Options::default()).unwrap();
+  let import_urls: Vec<&str> = output.deps.iter().map(|dep| dep.import_url.as_str()).collect();
+  assert_eq!(import_urls, vec!["./locales/*.ts", "./components/*.tsx"]);
+}
+
+#[test]
+fn asset_modules() {
+  let json = r#"{ "name": "aleph", "dev-deps": { "deno": "1.31" }, "default": true, "list": [1, 2] }"#;
+  let output = crate::transform("./deno.json", json, &Options::default()).unwrap();
+  assert_eq!(
+    output.code,
+    "export const name = \"aleph\";\nexport const list = [\n  1,\n  2\n];\nexport default {\n  name,\n  \"dev-deps\": {\n    \"deno\": \"1.31\"\n  },\n  \"default\": true,\n  list,\n};\n"
+  );
+  let options = Options {
+    minify: Some(MinifierOptions { compress: Some(true) }),
+    ..Default::default()
+  };
+  let output = crate::transform("./deno.json", json, &options).unwrap();
+  assert_eq!(
+    output.code,
+    "export const name=\"aleph\";export const list=[1,2];export default{name,\"dev-deps\":{\"deno\":\"1.31\"},\"default\":true,list};"
+  );
+  let output = crate::transform("./data.json", "[{ \"__proto__\": {} }]", &options).unwrap();
+  assert_eq!(output.code, "export default JSON.parse(\"[{\\\"__proto__\\\":{}}]\");");
+  let err = crate::transform("./data.json", "{\n  \"a\": 1,\n}", &options).unwrap_err();
+  assert!(
+    matches!(err, Error::Diagnostics(ref d) if d[0].line == Some(3) && d[0].code.as_deref() == Some("InvalidJson"))
+  );
+
+  let output = crate::transform("./README.md?raw", "# Aleph\n\"hello\"", &Options::default()).unwrap();
+  assert_eq!(output.code, "export default \"# Aleph\\n\\\"hello\\\"\";\n");
+  let output = crate::transform("./notes.txt", "hello", &Options::default()).unwrap();
+  assert_eq!(output.code, "export default \"hello\";\n");
+
+  let png = [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
+  let output = crate::transform_asset("./assets/logo.png", &png, &Options::default()).unwrap();
+  assert_eq!(output.code, "export default \"data:image/png;base64,iVBORw0KGgo=\";\n");
+  let options = Options {
+    asset_inline_limit: Some(4),
+    ..Default::default()
+  };
+  let output = crate::transform_asset("./assets/logo.png?v=1", &png, &options).unwrap();
+  assert_eq!(output.code, "export default \"/assets/logo.png\";\n");
+  let output = crate::transform_asset("./app.ts", b"export const a: number = 1", &options).unwrap();
+  assert_eq!(output.code, "export const a = 1;\n");
+}
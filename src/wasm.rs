@@ -0,0 +1,164 @@
+use crate::{Error, Options, ResolveHook};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::sync::Arc;
+use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
+use wasm_bindgen::JsCast;
+
+#[wasm_bindgen(js_name = "parseDeps")]
+pub fn parse_deps(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options = parse_options(options)?;
+  let deps = crate::parse_deps(specifier, code, &options);
+  rethrow_hook_exception()?;
+  let deps = deps.map_err(js_error)?;
+
+  Ok(to_js_value(&deps)?)
+}
+
+#[wasm_bindgen(js_name = "analyzeExports")]
+pub fn analyze_exports(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options = parse_options(options)?;
+  let exports = crate::analyze_exports(specifier, code, &options);
+  rethrow_hook_exception()?;
+  let exports = exports.map_err(js_error)?;
+
+  Ok(to_js_value(&exports)?)
+}
+
+#[wasm_bindgen(js_name = "transform")]
+pub fn transform(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options = parse_options(options)?;
+  let output = crate::transform(specifier, code, &options);
+  rethrow_hook_exception()?;
+  let output = output.map_err(js_error)?;
+
+  Ok(to_js_value(&output)?)
+}
+
+#[wasm_bindgen(js_name = "transformAsset")]
+pub fn transform_asset(specifier: &str, data: &[u8], options: JsValue) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options = parse_options(options)?;
+  let output = crate::transform_asset(specifier, data, &options);
+  rethrow_hook_exception()?;
+  let output = output.map_err(js_error)?;
+
+  Ok(to_js_value(&output)?)
+}
+
+#[wasm_bindgen(js_name = "parcelCSS")]
+pub fn parcel_css(filename: &str, code: &str, config_raw: JsValue) -> Result<JsValue, JsValue> {
+  let config: crate::CssConfig = serde_wasm_bindgen::from_value(config_raw)?;
+  let res = crate::compile_css(filename, code, &config).map_err(js_error)?;
+  Ok(serde_wasm_bindgen::to_value(&res)?)
+}
+
+/// Serialize the value to a JS value, maps are serialized as plain objects instead of `Map`s.
+fn to_js_value<T: Serialize>(value: &T) -> Result<JsValue, serde_wasm_bindgen::Error> {
+  value.serialize(&serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true))
+}
+
+/// Converts a compile error to a JS `Error` object, the parser diagnostics are attached as
+/// the `diagnostics` property, and the code frame of the first diagnostic as `codeFrame`.
+fn js_error(err: Error) -> JsValue {
+  let error = js_sys::Error::new(&err.to_string());
+  if let Error::Diagnostics(diagnostics) = &err {
+    if let Ok(diagnostics) = serde_wasm_bindgen::to_value(diagnostics) {
+      js_sys::Reflect::set(&error, &"diagnostics".into(), &diagnostics).ok();
+    }
+    if let Some(code_frame) = diagnostics.iter().find_map(|d| d.code_frame.as_ref()) {
+      js_sys::Reflect::set(&error, &"codeFrame".into(), &code_frame.into()).ok();
+    }
+  }
+  error.into()
+}
+
+thread_local! {
+  /// The first exception thrown by the `resolveHook` during the current call.
+  static HOOK_EXCEPTION: RefCell<Option<JsValue>> = const { RefCell::new(None) };
+}
+
+/// Rethrow the exception thrown by the `resolveHook`, the resolving falls back to the default
+/// when the hook throws, so the call needs to be failed afterwards.
+fn rethrow_hook_exception() -> Result<(), JsValue> {
+  match HOOK_EXCEPTION.with(|exception| exception.borrow_mut().take()) {
+    Some(err) => Err(err),
+    None => Ok(()),
+  }
+}
+
+/// The resolve hook provided by the JS host with the `resolveHook` option.
+#[cfg(not(feature = "parallel"))]
+struct JsResolveHook {
+  resolve: Option<js_sys::Function>,
+  after_resolve: Option<js_sys::Function>,
+}
+
+#[cfg(not(feature = "parallel"))]
+impl JsResolveHook {
+  fn call(func: &Option<js_sys::Function>, url: &str, referrer: &str) -> Option<String> {
+    match func.as_ref()?.call2(&JsValue::NULL, &url.into(), &referrer.into()) {
+      Ok(value) => value.as_string(),
+      Err(err) => {
+        HOOK_EXCEPTION.with(|exception| {
+          exception.borrow_mut().get_or_insert(err);
+        });
+        None
+      }
+    }
+  }
+}
+
+#[cfg(not(feature = "parallel"))]
+impl ResolveHook for JsResolveHook {
+  fn resolve(&self, specifier: &str, referrer: &str) -> Option<String> {
+    Self::call(&self.resolve, specifier, referrer)
+  }
+
+  fn after_resolve(&self, url: &str, referrer: &str) -> Option<String> {
+    Self::call(&self.after_resolve, url, referrer)
+  }
+}
+
+/// Deserialize the options, the `resolveHook` functions are taken out since they can't be deserialized.
+fn parse_options(options: JsValue) -> Result<Options, JsValue> {
+  if !options.is_object() {
+    return Ok(serde_wasm_bindgen::from_value(options)?);
+  }
+  let key = JsValue::from("resolveHook");
+  let hook = js_sys::Reflect::get(&options, &key)?;
+  let copy = js_sys::Object::assign(&js_sys::Object::new(), options.unchecked_ref());
+  js_sys::Reflect::delete_property(&copy, &key)?;
+  let mut options: Options = serde_wasm_bindgen::from_value(copy.into())?;
+  if hook.is_object() {
+    options.resolve_hook = Some(js_resolve_hook(&hook)?);
+  }
+  Ok(options)
+}
+
+// the JS functions can't be shared between threads, the hook is not supported by the `parallel` build
+// that requires the hook to be `Send + Sync`.
+#[cfg(feature = "parallel")]
+fn js_resolve_hook(_hook: &JsValue) -> Result<Arc<dyn ResolveHook>, JsValue> {
+  Err(js_sys::Error::new("The `resolveHook` option is not supported by the parallel build").into())
+}
+
+#[cfg(not(feature = "parallel"))]
+fn js_resolve_hook(hook: &JsValue) -> Result<Arc<dyn ResolveHook>, JsValue> {
+  let get_fn = |name: &str| {
+    js_sys::Reflect::get(hook, &name.into())
+      .ok()
+      .and_then(|f| f.dyn_into::<js_sys::Function>().ok())
+  };
+  Ok(Arc::new(JsResolveHook {
+    resolve: get_fn("resolve"),
+    after_resolve: get_fn("afterResolve"),
+  }))
+}
@@ -13,10 +13,43 @@ export type TransformOptions = {
   lang?: "ts" | "tsx" | "js" | "jsx";
   target?: EsmaVersion;
   importMap?: string;
+  /** The base URL to resolve the import map with, default is `file:///`. */
+  importMapBaseUrl?: string;
+  /** The CDN origin to rewrite `npm:`, `jsr:` and `node:` specifiers to, e.g. `https://esm.sh`. */
+  cdnOrigin?: string;
   globalVersion?: string;
   graphVersions?: Record<string, string>;
   resolveRemoteModule?: boolean;
-  stripDataExport?: boolean;
+  /**
+   * Strip the server-only exports for the client, `true` strips `data`, `mutation`, `GET`, `POST`,
+   * `PUT`, `PATCH` and `DELETE`, or specify the export names and/or a RegExp pattern.
+   */
+  stripDataExport?: boolean | string[] | { names?: string[]; pattern?: string };
+  /**
+   * The build target of React Server Components. With `"server"`, the exports of `"use client"` modules
+   * are replaced with `registerClientReference(specifier, name)` stubs; with `"client"`, the exports of
+   * `"use server"` modules are replaced with `createServerReference(specifier, name)` stubs. The stub
+   * functions are imported from `rscRuntime`, which is required with this option.
+   */
+  rsc?: "server" | "client";
+  /**
+   * The URL or import map specifier of the module exporting the `registerClientReference` and
+   * `createServerReference` functions used by the `rsc` stubs.
+   */
+  rscRuntime?: string;
+  /**
+   * Replace the global identifiers and member chains with the JSON values at compile time, e.g.
+   * `{ "process.env.NODE_ENV": "production", "import.meta.env.MODE": "production", "__DEV__": false }`.
+   * The unreachable branches and their imports are removed.
+   */
+  define?: Record<string, unknown>;
+  /**
+   * The candidate files to expand the `import.meta.glob` calls, relative to the root,
+   * e.g. `["./routes/index.tsx", "./routes/about.tsx"]`.
+   */
+  globFiles?: string[];
+  /** The binary assets smaller than the limit are inlined as data URLs, default is 4096 bytes. */
+  assetInlineLimit?: number;
   isDev?: boolean;
   reactRefresh?: boolean;
   sourceMap?: boolean;
@@ -25,19 +58,147 @@ export type TransformOptions = {
   jsxPragmaFrag?: string;
   jsxImportSource?: string;
   minify?: { compress: boolean };
+  /**
+   * Whether to return a best-effort result for a module with syntax errors, useful for dev server
+   * overlays. The syntax errors are reported in `errors` of the result, and the lines of the errors
+   * that can't be recovered by the parser are skipped. Otherwise an unrecoverable error is thrown and
+   * the recoverable ones are reported in `warnings`.
+   */
+  errorRecovery?: boolean;
+  /** The hook to customize how import/export URLs are resolved. */
+  resolveHook?: ResolveHook;
+};
+
+export type ExportDescriptor = {
+  /** The exported name, `default` for the default export and `*` for `export * from "..."`. */
+  readonly name: string;
+  readonly kind:
+    | "function"
+    | "class"
+    | "const"
+    | "let"
+    | "var"
+    | "enum"
+    | "value"
+    | "reExport";
+  readonly isAsync?: boolean;
+  /** The source of the re-export. */
+  readonly source?: string;
+};
+
+export type ParseDepsOptions =
+  & Pick<
+    TransformOptions,
+    | "importMap"
+    | "importMapBaseUrl"
+    | "cdnOrigin"
+    | "lang"
+    | "errorRecovery"
+    | "resolveHook"
+    | "globFiles"
+  >
+  & {
+    /**
+     * Scan the dependencies with the lexer only instead of parsing the whole module, it's much faster
+     * for crawling large module graphs, but only recognizes `import`/`export` statements, dynamic imports
+     * with string literals, workers, `new URL("...", import.meta.url)` and `import.meta.resolve("...")`.
+     * `import.meta.glob(...)` calls are not expanded, so the `globFiles` option has no effect in this mode.
+     */
+    lexerOnly?: boolean;
+    /** Include the `import type` and `export type` dependencies, they are skipped by default. */
+    typeOnlyDeps?: boolean;
+  };
+
+/**
+ * A hook to customize resolving, e.g. aliasing, virtual modules and monorepo packages.
+ * An exception thrown by the hook is rethrown by the compiler call.
+ */
+export type ResolveHook = {
+  /**
+   * Called before the import map is applied, returns a new specifier to replace
+   * the original one, or `undefined` to keep it.
+   */
+  resolve?(specifier: string, referrer: string): string | undefined;
+  /**
+   * Called with the URL resolved by the import map, returns a new URL to replace it,
+   * or `undefined` to keep it.
+   */
+  afterResolve?(url: string, referrer: string): string | undefined;
 };
 
 export type TransformResult = {
   readonly code: string;
   readonly map?: string;
   readonly deps?: DependencyDescriptor[];
+  /** The `"use client"` and `"use server"` directives of the module. */
+  readonly directives?: ("use client" | "use server")[];
+  /** The non-fatal issues, e.g. unresolvable specifiers. */
+  readonly warnings?: Diagnostic[];
+  /** The recovered syntax errors, if `errorRecovery` is enabled. */
+  readonly errors?: Diagnostic[];
 };
 
+export type Position = {
+  /** 1-based line number. */
+  readonly line: number;
+  /** 0-based column number. */
+  readonly column: number;
+  /** 0-based offset in UTF-16 code units. */
+  readonly utf16Offset: number;
+};
+
+export type DependencyKind =
+  | "import"
+  | "reExport"
+  | "exportAll"
+  | "dynamicImport"
+  | "worker"
+  | "sharedWorker"
+  | "serviceWorker"
+  | "sideEffect"
+  | "asset"
+  | "dynamicGlob";
+
 export type DependencyDescriptor = {
   readonly specifier: string;
   readonly importUrl: string;
-  readonly loc?: { start: number; end: number; ctxt: number };
+  readonly kind: DependencyKind;
+  /** The location of the specifier string, only available with `parseDeps`. */
+  readonly loc?: { start: Position; end: Position };
   readonly dynamic?: boolean;
+  /** The names imported from the dependency, `default` for the default import and `*` for the namespace. */
+  readonly names?: string[];
+  readonly typeOnly?: boolean;
+  /** The import assertions/attributes, e.g. `{ type: "json" }`. */
+  readonly attributes?: Record<string, string>;
+  /** The integrity metadata from the import map. */
+  readonly integrity?: string;
+  /** The static prefix and suffix of a `dynamicGlob` dependency, e.g. `./locales/` and `.ts`. */
+  readonly glob?: { prefix: string; suffix: string };
+};
+
+/** A diagnostic message reported by the compiler. */
+export type Diagnostic = {
+  readonly message: string;
+  readonly specifier: string;
+  readonly severity: "error" | "warning";
+  /** The error code, e.g. `ExpectedSemiForExprStmt`. */
+  readonly code?: string;
+  /** The line number (1-based). */
+  readonly line?: number;
+  /** The column number (0-based), in characters. */
+  readonly column?: number;
+  /** The offsets in the source code, in UTF-16 code units like the JS string indexes. */
+  readonly span?: { start: number; end: number };
+  /** The surrounding source code with a caret under the offending span. */
+  readonly codeFrame?: string;
+};
+
+/** The error thrown by the compiler, `diagnostics` is present if the module can't be parsed. */
+export type CompileError = Error & {
+  readonly diagnostics?: Diagnostic[];
+  /** The code frame of the first diagnostic. */
+  readonly codeFrame?: string;
 };
 
 export interface Targets {
@@ -85,6 +246,11 @@ export interface TransformCSSOptions {
    * selectors but individual names (without any . or # prefixes).
    */
   unusedSymbols?: string[];
+  /**
+   * Whether to ignore invalid rules and declarations rather than erroring.
+   * The recovered errors are returned in `warnings` of the result.
+   */
+  errorRecovery?: boolean;
 }
 
 export interface Drafts {
@@ -111,6 +277,8 @@ export interface TransformCSSResult {
   readonly exports?: CSSModuleExports;
   /** `@import` and `url()` dependencies, if enabled. */
   readonly dependencies?: Dependency[];
+  /** The non-fatal issues, e.g. unsupported pseudo classes. */
+  readonly warnings?: Diagnostic[];
 }
 
 export interface CSSModulesConfig {